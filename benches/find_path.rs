@@ -0,0 +1,55 @@
+//! Path finding on open, walled, costly and closed maps, run with `cargo bench`.
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rougepush::vectors::{utils::find_path, Vector2Int};
+
+const SIZE: i32 = 49;
+
+fn inside(v: Vector2Int) -> bool {
+    (0..SIZE).contains(&v.x) && (0..SIZE).contains(&v.y)
+}
+
+fn open(v: Vector2Int) -> Option<u32> {
+    inside(v).then_some(1)
+}
+
+/// Walls on every other column with a gap alternating between the top and the bottom.
+fn serpentine(v: Vector2Int) -> Option<u32> {
+    let gap = if v.x % 4 == 1 { SIZE - 1 } else { 0 };
+    (inside(v) && (v.x % 2 == 0 || v.y == gap)).then_some(1)
+}
+
+/// Floor mixed with water and spikes.
+fn costly(v: Vector2Int) -> Option<u32> {
+    inside(v).then(|| ((v.x * 7 + v.y * 13) % 4) as u32 + 1)
+}
+
+/// Target in the middle surrounded by walls.
+fn closed(v: Vector2Int) -> Option<u32> {
+    let center = Vector2Int::new(SIZE / 2, SIZE / 2);
+    (inside(v) && v.manhattan(center) != 2).then_some(1)
+}
+
+fn bench_find_path(c: &mut Criterion) {
+    let corner = Vector2Int::new(0, 0);
+    let far_corner = Vector2Int::new(SIZE - 1, SIZE - 1);
+    let center = Vector2Int::new(SIZE / 2, SIZE / 2);
+    let limit = (SIZE * SIZE) as usize;
+    c.bench_function("open", |b| {
+        b.iter(|| find_path(black_box(corner), black_box(far_corner), limit, open))
+    });
+    c.bench_function("serpentine", |b| {
+        b.iter(|| find_path(black_box(corner), black_box(far_corner), limit, serpentine))
+    });
+    c.bench_function("costly", |b| {
+        b.iter(|| find_path(black_box(corner), black_box(far_corner), limit, costly))
+    });
+    c.bench_function("unreachable", |b| {
+        b.iter(|| find_path(black_box(corner), black_box(center), limit, closed))
+    });
+    c.bench_function("cut off by max distance", |b| {
+        b.iter(|| find_path(black_box(corner), black_box(far_corner), 100, serpentine))
+    });
+}
+
+criterion_group!(benches, bench_find_path);
+criterion_main!(benches);
@@ -0,0 +1,88 @@
+use std::ops::Deref;
+
+use super::Action;
+use crate::{
+    board::{
+        change_tile,
+        components::*,
+        state::{BoardQuery, WorldBoard},
+    },
+    input::InputAction,
+    messages::{self, MessageCategory},
+    rules,
+    states::*,
+    vectors::Vector2Int,
+};
+use bevy::prelude::*;
+
+/// Blows up the cracked wall with one of the carried bombs.
+#[derive(Clone, Copy)]
+pub struct BlastWallAction(pub Entity, pub Vector2Int, pub InputAction);
+
+impl BlastWallAction {
+    pub fn register(app: &mut App) {
+        app.add_systems(
+            OnEnter(GameTurnSteps::ActionSelection),
+            (Self::trim_solid_walls).in_set(PreparingActions::FindWrongMoves),
+        );
+    }
+
+    pub fn trim_solid_walls(
+        mut q: Query<(&PossibleActions, &mut ActionsToRemove, Option<&Bombs>)>,
+        board: BoardQuery,
+    ) {
+        let Ok((actions, mut to_remove, bombs)) = q.single_mut() else {
+            return;
+        };
+        let bombs = bombs.map_or(0, |b| b.0);
+        let actions = actions.deref().deref();
+        let mut wrong_actions = Vec::new();
+        for (index, boxed_action) in actions.iter().enumerate() {
+            let Some(action) = boxed_action.as_any().downcast_ref::<BlastWallAction>() else {
+                continue;
+            };
+            if !rules::can_blast(&board, action.1, bombs) {
+                wrong_actions.push(index);
+            }
+        }
+        to_remove.0.append(&mut wrong_actions);
+    }
+}
+
+impl Action for BlastWallAction {
+    fn get_input(&self) -> Option<InputAction> {
+        Some(self.2)
+    }
+    fn actor(&self) -> Option<Entity> {
+        Some(self.0)
+    }
+    fn execute(&self, world: &mut World) -> bool {
+        let bombs = world.get::<Bombs>(self.0).map_or(0, |b| b.0);
+        if !rules::can_blast(&WorldBoard(world), self.1, bombs) {
+            return false;
+        }
+        if !change_tile(world, self.1, TileType::BaseFloor) {
+            return false;
+        }
+        if let Some(mut bombs) = world.get_mut::<Bombs>(self.0) {
+            **bombs -= 1;
+        }
+        messages::log(
+            world,
+            MessageCategory::Info,
+            "The bomb blows up the cracked wall",
+        );
+
+        true
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn action_type(&self) -> super::ActionType {
+        super::ActionType::BlastWall
+    }
+    fn target_pos(&self) -> Option<Vector2Int> {
+        Some(self.1)
+    }
+}
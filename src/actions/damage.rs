@@ -1,5 +1,11 @@
 use super::Action;
-use crate::{board::components::*, input::InputAction, vectors::Vector2Int};
+use crate::{
+    board::components::*,
+    input::InputAction,
+    messages::{self, MessageCategory},
+    rules,
+    vectors::Vector2Int,
+};
 use bevy::prelude::*;
 
 pub struct DamageAction(pub Entity, pub u32);
@@ -8,8 +14,24 @@ impl Action for DamageAction {
         let Some(mut health) = world.get_mut::<Health>(self.0) else {
             return false;
         };
-        health.value = health.value.saturating_sub(self.1);
-        if health.value == 0 {
+        health.value = rules::apply_damage(health.value, self.1);
+        let health = health.value;
+        let pos = world
+            .get::<PiecePos>(self.0)
+            .map_or(Vector2Int::default(), |p| p.0);
+        world.send_event(PieceDamaged {
+            piece: self.0,
+            pos,
+            amount: self.1,
+            health,
+        });
+        if health == 0 {
+            let (category, text) = if messages::is_player(world, self.0) {
+                (MessageCategory::Danger, "You die")
+            } else {
+                (MessageCategory::Combat, "The enemy dies")
+            };
+            messages::log(world, category, text);
             world
                 .entity_mut(self.0)
                 .remove::<Health>()
@@ -24,6 +46,9 @@ impl Action for DamageAction {
     fn get_input(&self) -> Option<InputAction> {
         None
     }
+    fn actor(&self) -> Option<Entity> {
+        None
+    }
     fn action_type(&self) -> super::ActionType {
         super::ActionType::Damage
     }
@@ -0,0 +1,78 @@
+use std::ops::Deref;
+
+use super::Action;
+use crate::{
+    board::{
+        components::*,
+        state::{BoardQuery, WorldBoard},
+    },
+    input::InputAction,
+    messages::{self, MessageCategory},
+    rules,
+    states::*,
+    vectors::Vector2Int,
+};
+use bevy::prelude::*;
+
+/// Makes the discovered trap harmless, the piece stays in place.
+#[derive(Clone, Copy)]
+pub struct DisarmTrapAction(pub Entity, pub Vector2Int, pub InputAction);
+
+impl DisarmTrapAction {
+    pub fn register(app: &mut App) {
+        app.add_systems(
+            OnEnter(GameTurnSteps::ActionSelection),
+            (Self::trim_unknown_traps).in_set(PreparingActions::FindWrongMoves),
+        );
+    }
+
+    pub fn trim_unknown_traps(
+        mut q: Query<(&PossibleActions, &mut ActionsToRemove)>,
+        board: BoardQuery,
+    ) {
+        let Ok((actions, mut to_remove)) = q.single_mut() else {
+            return;
+        };
+        let actions = actions.deref().deref();
+        let mut wrong_actions = Vec::new();
+        for (index, boxed_action) in actions.iter().enumerate() {
+            let Some(action) = boxed_action.as_any().downcast_ref::<DisarmTrapAction>() else {
+                continue;
+            };
+            if !rules::can_disarm(&board, action.1) {
+                wrong_actions.push(index);
+            }
+        }
+        to_remove.0.append(&mut wrong_actions);
+    }
+}
+
+impl Action for DisarmTrapAction {
+    fn get_input(&self) -> Option<InputAction> {
+        Some(self.2)
+    }
+    fn actor(&self) -> Option<Entity> {
+        Some(self.0)
+    }
+    fn execute(&self, world: &mut World) -> bool {
+        if !rules::can_disarm(&WorldBoard(world), self.1) {
+            return false;
+        }
+        let trap = world.resource::<TrapIndex>().get(self.1);
+        if let Some(mut trap) = trap.and_then(|entity| world.get_mut::<Trap>(entity)) {
+            trap.armed = false;
+        }
+        messages::log(world, MessageCategory::Discovery, "You disarm the trap");
+        true
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn action_type(&self) -> super::ActionType {
+        super::ActionType::DisarmTrap
+    }
+    fn target_pos(&self) -> Option<Vector2Int> {
+        Some(self.1)
+    }
+}
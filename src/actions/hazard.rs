@@ -0,0 +1,155 @@
+use super::{damage::DamageAction, Action};
+use crate::{
+    board::{components::*, state::WorldBoard},
+    input::InputAction,
+    messages::{self, MessageCategory},
+    rules::{self, TileEffect},
+    states::*,
+    vectors::Vector2Int,
+};
+use bevy::prelude::*;
+
+/// Effect of the tile entered by the piece, the last field is the direction it was moving in.
+pub struct HazardAction(pub Entity, pub Vector2Int, pub Vector2Int);
+
+impl HazardAction {
+    pub fn register(app: &mut App) {
+        app.add_systems(
+            OnEnter(GameTurnSteps::PerformAction),
+            Self::burn_current_actor,
+        );
+    }
+
+    /// Burning pieces take the damage after each of their actions.
+    pub fn burn_current_actor(
+        mut commands: Commands,
+        mut q: Query<(Entity, &mut Burning), With<CurrentActorToken>>,
+        mut pending_actions: ResMut<PendingActions>,
+    ) {
+        // undo passes the empty queue, nothing happens in that turn
+        if pending_actions.is_empty() {
+            return;
+        }
+        let Ok((entity, mut burning)) = q.single_mut() else {
+            return;
+        };
+        pending_actions.push_back(Box::new(DamageAction(entity, rules::BURNING_DAMAGE)));
+        **burning = burning.saturating_sub(1);
+        if **burning == 0 {
+            commands.entity(entity).remove::<Burning>();
+        }
+    }
+}
+
+impl HazardAction {
+    fn log_effect(&self, world: &mut World, effect: TileEffect, tile: TileType, was_burning: bool) {
+        let player = messages::is_player(world, self.0);
+        let name = messages::piece_name(world, self.0);
+        let (category, text) = match (effect, tile) {
+            (TileEffect::Kill, TileType::Pit) => (
+                MessageCategory::Combat,
+                format!(
+                    "{} {} into the pit",
+                    name,
+                    messages::verb(world, self.0, "fall", "falls")
+                ),
+            ),
+            (TileEffect::Kill, _) => (
+                MessageCategory::Combat,
+                format!(
+                    "{} {} in the lava",
+                    name,
+                    messages::verb(world, self.0, "burn", "burns")
+                ),
+            ),
+            // hazards hurting the enemies out of sight would only flood the log
+            _ if !player => return,
+            (TileEffect::Hurt(_), _) => (MessageCategory::Danger, "You step on the spikes".into()),
+            (TileEffect::Ignite, _) => (MessageCategory::Danger, "You catch fire".into()),
+            (TileEffect::Soak, _) if was_burning => {
+                (MessageCategory::Info, "The water puts out the fire".into())
+            }
+            (TileEffect::Soak, _) => (MessageCategory::Info, "You wade through the water".into()),
+            (TileEffect::Slide, _) => (MessageCategory::Info, "You slide on the ice".into()),
+        };
+        let category = if player && category == MessageCategory::Combat {
+            MessageCategory::Danger
+        } else {
+            category
+        };
+        messages::log(world, category, text);
+    }
+}
+
+impl Action for HazardAction {
+    fn execute(&self, world: &mut World) -> bool {
+        let Some(pos) = world.get::<PiecePos>(self.0).map(|p| p.0) else {
+            return false;
+        };
+        // the piece was teleported away
+        if pos != self.1 {
+            return true;
+        }
+        // the piece died on the way
+        let Some(health) = world.get::<Health>(self.0).map(|h| h.value) else {
+            return true;
+        };
+        let is_flying = world.get::<Flying>(self.0).is_some();
+        let board = WorldBoard(world);
+        let Some(tile) = rules::BoardState::tile(&board, self.1).cloned() else {
+            return false;
+        };
+        let effect = rules::tile_effect(&tile, is_flying);
+        let slide_target = rules::slide_target(&board, self.1, self.2);
+        let was_burning = world.get::<Burning>(self.0).is_some();
+        if let Some(effect) = effect {
+            self.log_effect(world, effect, tile, was_burning);
+        }
+        let consequence: Option<Box<dyn Action>> = match effect {
+            None => None,
+            Some(TileEffect::Hurt(damage)) => Some(Box::new(DamageAction(self.0, damage))),
+            Some(TileEffect::Kill) => Some(Box::new(DamageAction(self.0, health))),
+            Some(TileEffect::Ignite) => {
+                world
+                    .entity_mut(self.0)
+                    .insert(Burning(rules::BURNING_TURNS));
+                None
+            }
+            Some(TileEffect::Soak) => {
+                world.entity_mut(self.0).remove::<Burning>();
+                if let Some(mut turn) = world.get_mut::<ActorTurn>(self.0) {
+                    **turn += rules::WATER_DELAY;
+                }
+                None
+            }
+            Some(TileEffect::Slide) => {
+                if let Some(target) = slide_target {
+                    world.entity_mut(self.0).insert(PiecePos(target));
+                    super::enter_tile(world, self.0, target, self.2);
+                }
+                None
+            }
+        };
+        if let (Some(action), Some(mut pending_actions)) =
+            (consequence, world.get_resource_mut::<PendingActions>())
+        {
+            pending_actions.push_back(action);
+        }
+        true
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn get_input(&self) -> Option<InputAction> {
+        None
+    }
+    fn actor(&self) -> Option<Entity> {
+        None
+    }
+    fn action_type(&self) -> super::ActionType {
+        super::ActionType::Hazard
+    }
+    fn target_pos(&self) -> Option<Vector2Int> {
+        Some(self.1)
+    }
+}
@@ -1,7 +1,17 @@
-use std::{collections::VecDeque, ops::Deref};
+use std::ops::Deref;
 
 use super::{damage::DamageAction, Action};
-use crate::{board::components::*, input::InputAction, states::*, vectors::Vector2Int};
+use crate::{
+    board::{
+        components::*,
+        state::{BoardQuery, WorldBoard},
+    },
+    input::InputAction,
+    messages::{self, MessageCategory},
+    rules,
+    states::*,
+    vectors::Vector2Int,
+};
 use bevy::prelude::*;
 
 pub struct MeleeHitAction {
@@ -16,24 +26,34 @@ impl Action for MeleeHitAction {
         let Some(attacker_position) = world.get::<PiecePos>(self.attacker) else {
             return false;
         };
-        if attacker_position.manhattan(self.target) > 1 {
+        let Some(target) = rules::melee_target(
+            &WorldBoard(world),
+            &self.attacker_type,
+            attacker_position.0,
+            self.target,
+        ) else {
             return false;
         };
-        let target_entities = world
-            .query_filtered::<(Entity, &PiecePos, &Piece), With<Health>>()
-            .iter(world)
-            .filter(|(_, p, piece)| p.0 == self.target && &self.attacker_type != *piece)
-            .collect::<Vec<_>>();
-        if target_entities.is_empty() {
-            return false;
+        let text = format!(
+            "{} {} {} for {}",
+            messages::piece_name(world, self.attacker),
+            messages::verb(world, self.attacker, "hit", "hits"),
+            messages::piece_name(world, target).to_lowercase(),
+            self.damage
+        );
+        let category = if messages::is_player(world, target) {
+            MessageCategory::Danger
+        } else {
+            MessageCategory::Combat
         };
-        let mut result = target_entities
-            .iter()
-            .map(|e| Box::new(DamageAction(e.0, self.damage)) as Box<dyn Action>)
-            .collect::<VecDeque<_>>();
+        messages::log(world, category, text);
         if let Some(mut pending_actions) = world.get_resource_mut::<PendingActions>() {
-            pending_actions.append(&mut result);
+            pending_actions.push_back(Box::new(DamageAction(target, self.damage)));
         }
+        world.send_event(MeleeAttack {
+            attacker: self.attacker,
+            target: self.target,
+        });
         true
     }
     fn as_any(&self) -> &dyn std::any::Any {
@@ -43,6 +63,9 @@ impl Action for MeleeHitAction {
     fn get_input(&self) -> Option<InputAction> {
         self.key
     }
+    fn actor(&self) -> Option<Entity> {
+        Some(self.attacker)
+    }
     fn action_type(&self) -> super::ActionType {
         super::ActionType::MeleeeHit
     }
@@ -60,10 +83,10 @@ impl MeleeHitAction {
     }
 
     pub fn trim_attacks_without_enemies(
-        mut q: Query<(&PossibleActions, &mut ActionsToRemove)>,
-        other_pieces: Query<(&PiecePos, &Piece), With<Health>>,
+        mut q: Query<(&PiecePos, &PossibleActions, &mut ActionsToRemove)>,
+        board: BoardQuery,
     ) {
-        let Ok((actions, mut to_remove)) = q.single_mut() else {
+        let Ok((position, actions, mut to_remove)) = q.single_mut() else {
             return;
         };
         let actions = actions.deref().deref();
@@ -72,14 +95,9 @@ impl MeleeHitAction {
             let Some(action) = boxed_action.as_any().downcast_ref::<MeleeHitAction>() else {
                 continue;
             };
-            let mut is_valid_move = false;
-            if other_pieces
-                .iter()
-                .any(|(p, piece)| **p == action.target && action.attacker_type != *piece)
+            if rules::melee_target(&board, &action.attacker_type, position.0, action.target)
+                .is_none()
             {
-                is_valid_move = true;
-            }
-            if !is_valid_move {
                 wrong_actions.push(index);
             }
         }
@@ -1,16 +1,29 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
 
-use crate::{input::InputAction, vectors::Vector2Int};
+use crate::{input::InputAction, states::PendingActions, vectors::Vector2Int};
 
-use self::{melee_hit::MeleeHitAction, walk::WalkAction};
+use self::{
+    blast_wall::BlastWallAction, disarm_trap::DisarmTrapAction, hazard::HazardAction,
+    melee_hit::MeleeHitAction, open_door::OpenDoorAction, search::SearchAction,
+    trap::TriggerTrapAction, walk::WalkAction,
+};
 
+pub mod blast_wall;
 pub mod damage;
+pub mod disarm_trap;
+pub mod hazard;
 pub mod melee_hit;
+pub mod open_door;
+pub mod search;
+pub mod trap;
 pub mod walk;
 
 pub trait Action: Send + Sync {
     fn get_input(&self) -> Option<InputAction>;
+    /// Entity that performs the action, `None` for consequences of other actions.
+    fn actor(&self) -> Option<Entity>;
     fn execute(&self, world: &mut World) -> bool;
     fn as_any(&self) -> &dyn Any;
     fn action_type(&self) -> ActionType;
@@ -25,13 +38,35 @@ impl RegisterActions for App {
     fn register_all_actions(&mut self) -> &mut Self {
         WalkAction::register(self);
         MeleeHitAction::register(self);
+        OpenDoorAction::register(self);
+        HazardAction::register(self);
+        DisarmTrapAction::register(self);
+        SearchAction::register(self);
+        BlastWallAction::register(self);
         self
     }
 }
 
-#[derive(Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy, Reflect)]
+/// Queues what happens to the piece that entered the tile moving in the `direction`.
+pub fn enter_tile(world: &mut World, piece: Entity, pos: Vector2Int, direction: Vector2Int) {
+    let Some(mut pending_actions) = world.get_resource_mut::<PendingActions>() else {
+        return;
+    };
+    pending_actions.push_back(Box::new(HazardAction(piece, pos, direction)));
+    pending_actions.push_back(Box::new(TriggerTrapAction(piece, pos)));
+}
+
+#[derive(
+    Debug, Hash, Ord, PartialOrd, PartialEq, Eq, Clone, Copy, Reflect, Serialize, Deserialize,
+)]
 pub enum ActionType {
     Damage,
     MeleeeHit,
     Walk,
+    OpenDoor,
+    Hazard,
+    TriggerTrap,
+    DisarmTrap,
+    Search,
+    BlastWall,
 }
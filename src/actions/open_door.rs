@@ -0,0 +1,88 @@
+use std::ops::Deref;
+
+use super::Action;
+use crate::{
+    board::{
+        change_tile,
+        components::*,
+        state::{BoardQuery, WorldBoard},
+    },
+    input::InputAction,
+    messages::{self, MessageCategory},
+    rules::{self, BoardState},
+    states::*,
+    vectors::Vector2Int,
+};
+use bevy::prelude::*;
+
+#[derive(Clone, Copy)]
+pub struct OpenDoorAction(pub Entity, pub Vector2Int, pub InputAction);
+
+impl OpenDoorAction {
+    pub fn register(app: &mut App) {
+        app.add_systems(
+            OnEnter(GameTurnSteps::ActionSelection),
+            (Self::trim_missing_doors).in_set(PreparingActions::FindWrongMoves),
+        );
+    }
+
+    pub fn trim_missing_doors(
+        mut q: Query<(&PossibleActions, &mut ActionsToRemove, Option<&Keys>)>,
+        board: BoardQuery,
+    ) {
+        let Ok((actions, mut to_remove, keys)) = q.single_mut() else {
+            return;
+        };
+        let keys = keys.map_or(0, |k| k.0);
+        let actions = actions.deref().deref();
+        let mut wrong_actions = Vec::new();
+        for (index, boxed_action) in actions.iter().enumerate() {
+            let Some(action) = boxed_action.as_any().downcast_ref::<OpenDoorAction>() else {
+                continue;
+            };
+            if !rules::can_open(&board, action.1, keys) {
+                wrong_actions.push(index);
+            }
+        }
+        to_remove.0.append(&mut wrong_actions);
+    }
+}
+
+impl Action for OpenDoorAction {
+    fn get_input(&self) -> Option<InputAction> {
+        Some(self.2)
+    }
+    fn actor(&self) -> Option<Entity> {
+        Some(self.0)
+    }
+    fn execute(&self, world: &mut World) -> bool {
+        let keys = world.get::<Keys>(self.0).map_or(0, |k| k.0);
+        if !rules::can_open(&WorldBoard(world), self.1, keys) {
+            return false;
+        }
+        let locked = WorldBoard(world).tile(self.1) == Some(&TileType::LockedDoor);
+        if !change_tile(world, self.1, TileType::Door { open: true }) {
+            return false;
+        }
+        if let (true, Some(mut keys)) = (locked, world.get_mut::<Keys>(self.0)) {
+            **keys -= 1;
+            messages::log(
+                world,
+                MessageCategory::Item,
+                "You unlock the door with a key",
+            );
+        }
+
+        true
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn action_type(&self) -> super::ActionType {
+        super::ActionType::OpenDoor
+    }
+    fn target_pos(&self) -> Option<Vector2Int> {
+        Some(self.1)
+    }
+}
@@ -0,0 +1,86 @@
+use rand::Rng;
+
+use super::Action;
+use crate::{
+    board::components::*,
+    input::InputAction,
+    messages::{self, MessageCategory, MessageLog},
+    rules,
+    states::{MainGameState, TurnCounter},
+    vectors::Vector2Int,
+};
+use bevy::prelude::*;
+
+/// Looks around for the hidden traps instead of moving.
+#[derive(Clone, Copy)]
+pub struct SearchAction(pub Entity, pub InputAction);
+
+impl SearchAction {
+    pub fn register(app: &mut App) {
+        app.add_systems(
+            Update,
+            Self::notice_traps.run_if(in_state(MainGameState::Game)),
+        );
+    }
+
+    /// Passive perception, the player may notice the traps next to them after each move.
+    pub fn notice_traps(
+        player: Query<&PiecePos, (With<PlayerControl>, Changed<PiecePos>)>,
+        mut traps: Query<(&PiecePos, &mut Trap)>,
+        seed: Res<RunSeed>,
+        turn: Res<TurnCounter>,
+        mut log: ResMut<MessageLog>,
+    ) {
+        let Ok(player) = player.single() else {
+            return;
+        };
+        for (pos, mut trap) in traps.iter_mut() {
+            if trap.discovered || pos.manhattan(player.0) > 1 {
+                continue;
+            }
+            if rules::event_rng(**seed, **turn, pos.0).gen_bool(rules::PERCEPTION_CHANCE) {
+                info!("Noticed a trap at {:?}", pos.0);
+                log.push(**turn, MessageCategory::Discovery, "You notice a trap");
+                trap.discovered = true;
+            }
+        }
+    }
+}
+
+impl Action for SearchAction {
+    fn execute(&self, world: &mut World) -> bool {
+        let Some(from) = world.get::<PiecePos>(self.0).map(|p| p.0) else {
+            return false;
+        };
+        let mut traps = world.query::<(&PiecePos, &mut Trap)>();
+        let mut found = 0;
+        for (pos, mut trap) in traps.iter_mut(world) {
+            if !trap.discovered && pos.manhattan(from) <= rules::SEARCH_RADIUS {
+                trap.discovered = true;
+                found += 1;
+            }
+        }
+        let text = match found {
+            0 => "You find nothing".to_owned(),
+            1 => "You found a trap".to_owned(),
+            n => format!("You found {} traps", n),
+        };
+        messages::log(world, MessageCategory::Discovery, text);
+        true
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn get_input(&self) -> Option<InputAction> {
+        Some(self.1)
+    }
+    fn actor(&self) -> Option<Entity> {
+        Some(self.0)
+    }
+    fn action_type(&self) -> super::ActionType {
+        super::ActionType::Search
+    }
+    fn target_pos(&self) -> Option<Vector2Int> {
+        None
+    }
+}
@@ -0,0 +1,93 @@
+use super::{damage::DamageAction, Action};
+use crate::{
+    board::{components::*, state::WorldBoard},
+    input::InputAction,
+    messages::{self, MessageCategory},
+    rules,
+    states::*,
+    vectors::Vector2Int,
+};
+use bevy::prelude::*;
+
+/// Sets off the armed trap on the tile entered by the piece.
+pub struct TriggerTrapAction(pub Entity, pub Vector2Int);
+
+impl TriggerTrapAction {
+    fn teleport_target(&self, world: &World, trap_pos: Vector2Int) -> Option<Vector2Int> {
+        let board = world.get_resource::<CurrentBoard>()?;
+        let seed = world.get_resource::<RunSeed>().map_or(0, |s| **s);
+        let turn = world.get_resource::<TurnCounter>().map_or(0, |t| **t);
+        rules::teleport_target(&WorldBoard(world), board.tiles.iter(), seed, turn, trap_pos)
+    }
+}
+
+impl Action for TriggerTrapAction {
+    fn execute(&self, world: &mut World) -> bool {
+        let Some(kind) = world
+            .resource::<TrapIndex>()
+            .get(self.1)
+            .and_then(|entity| world.get_mut::<Trap>(entity))
+            .filter(|trap| trap.armed)
+            .map(|mut trap| {
+                trap.discovered = true;
+                trap.armed = !trap.kind.single_use();
+                trap.kind
+            })
+        else {
+            return true;
+        };
+        info!("Trap {:?} triggered at {:?}", kind, self.1);
+        let text = match kind {
+            TrapKind::Alarm => Some("An alarm wakes up the dungeon"),
+            _ if !messages::is_player(world, self.0) => None,
+            TrapKind::PressurePlate => Some("You step on a pressure plate"),
+            TrapKind::Dart => Some("A dart shoots out of the wall"),
+            TrapKind::Teleport => Some("The floor flashes and you are somewhere else"),
+        };
+        if let Some(text) = text {
+            messages::log(world, MessageCategory::Danger, text);
+        }
+        let alive = world.get::<Health>(self.0).is_some();
+        match kind {
+            TrapKind::PressurePlate | TrapKind::Dart => {}
+            TrapKind::Teleport => {
+                if let (true, Some(target)) = (alive, self.teleport_target(world, self.1)) {
+                    world.entity_mut(self.0).insert(PiecePos(target));
+                }
+            }
+            TrapKind::Alarm => {
+                let monsters: Vec<Entity> = world
+                    .query_filtered::<Entity, (With<AiControl>, With<Piece>)>()
+                    .iter(world)
+                    .collect();
+                for monster in monsters {
+                    world.entity_mut(monster).insert(Awake);
+                }
+            }
+        }
+        let damage = rules::trap_damage(kind);
+        if let (true, true, Some(mut pending_actions)) = (
+            alive,
+            damage > 0,
+            world.get_resource_mut::<PendingActions>(),
+        ) {
+            pending_actions.push_back(Box::new(DamageAction(self.0, damage)));
+        }
+        true
+    }
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+    fn get_input(&self) -> Option<InputAction> {
+        None
+    }
+    fn actor(&self) -> Option<Entity> {
+        None
+    }
+    fn action_type(&self) -> super::ActionType {
+        super::ActionType::TriggerTrap
+    }
+    fn target_pos(&self) -> Option<Vector2Int> {
+        Some(self.1)
+    }
+}
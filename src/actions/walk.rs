@@ -1,7 +1,16 @@
 use std::ops::Deref;
 
 use super::Action;
-use crate::{board::components::*, input::InputAction, states::*, vectors::Vector2Int};
+use crate::{
+    board::{
+        components::*,
+        state::{BoardQuery, WorldBoard},
+    },
+    input::InputAction,
+    rules,
+    states::*,
+    vectors::Vector2Int,
+};
 use bevy::prelude::*;
 
 #[derive(Clone, Copy)]
@@ -17,8 +26,7 @@ impl WalkAction {
 
     pub fn trim_moves_into_abyss(
         mut q: Query<(&PossibleActions, &mut ActionsToRemove)>,
-        other_pieces: Query<&PiecePos, With<Occupier>>,
-        board: Res<CurrentBoard>,
+        board: BoardQuery,
     ) {
         let Ok((actions, mut to_remove)) = q.single_mut() else {
             return;
@@ -29,16 +37,7 @@ impl WalkAction {
             let Some(action) = boxed_action.as_any().downcast_ref::<WalkAction>() else {
                 continue;
             };
-            let mut is_valid_move = false;
-            if let Some(tile) = board.tiles.get(&action.1) {
-                if tile == &TileType::BaseFloor {
-                    is_valid_move = true;
-                }
-            }
-            if other_pieces.iter().any(|p| p.0 == action.1) {
-                is_valid_move = false;
-            }
-            if !is_valid_move {
+            if !rules::can_walk(&board, action.1) {
                 wrong_actions.push(index);
             }
         }
@@ -50,24 +49,21 @@ impl Action for WalkAction {
     fn get_input(&self) -> Option<InputAction> {
         Some(self.2)
     }
+    fn actor(&self) -> Option<Entity> {
+        Some(self.0)
+    }
     fn execute(&self, world: &mut World) -> bool {
-        let Some(board) = world.get_resource::<CurrentBoard>() else {
-            return false;
-        };
-        if !board.tiles.contains_key(&self.1) {
+        if !rules::can_walk(&WorldBoard(world), self.1) {
             return false;
         };
-        if world
-            .query_filtered::<&PiecePos, With<Occupier>>()
-            .iter(world)
-            .any(|p| p.0 == self.1)
-        {
+        let Ok(mut entity) = world.get_entity_mut(self.0) else {
             return false;
         };
-        let Some(mut position) = world.get_mut::<PiecePos>(self.0) else {
+        let Some(from) = entity.get::<PiecePos>().map(|p| p.0) else {
             return false;
         };
-        position.0 = self.1;
+        entity.insert(PiecePos(self.1));
+        super::enter_tile(world, self.0, self.1, self.1 - from);
 
         true
     }
@@ -0,0 +1,164 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_sprite3d::Sprite3d;
+use bevy_tweening::{lens::TransformPositionLens, Animator, Tween};
+
+use super::{
+    components::{Animating, MeleeAttack, Piece, PieceDamaged},
+    MapTile, PiecePos,
+};
+
+const HOP_TIME: Duration = Duration::from_millis(80);
+const HOP_HEIGHT: f32 = 0.2;
+const LUNGE_TIME: Duration = Duration::from_millis(90);
+const LUNGE_REACH: f32 = 0.4;
+const FALL_TIME: Duration = Duration::from_millis(400);
+const FLASH_TIME: Duration = Duration::from_millis(150);
+const FLASH_COLOR: Color = Color::srgb(1.0, 0.3, 0.3);
+/// Pieces moved further than that are teleported, not animated.
+const MAX_HOP_DISTANCE: f32 = 1.5;
+
+/// Multiplier of the animations speed, set from the settings.
+#[derive(Resource, Reflect)]
+pub struct AnimationSpeed(pub f32);
+
+impl Default for AnimationSpeed {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl AnimationSpeed {
+    fn scale(&self, duration: Duration) -> Duration {
+        duration.div_f32(self.0.max(0.1))
+    }
+}
+
+/// Piece tinted after being hit, the color is restored when the timer finishes.
+#[derive(Component)]
+pub struct HitFlash(Timer);
+
+fn piece_translation(pos: &PiecePos) -> Vec3 {
+    Vec3::new(pos.x as f32, 0.5, pos.y as f32)
+}
+
+fn position_tween(duration: Duration, start: Vec3, end: Vec3) -> Tween<Transform> {
+    Tween::new(
+        EaseFunction::QuadraticInOut,
+        duration,
+        TransformPositionLens { start, end },
+    )
+}
+
+fn animate(commands: &mut Commands, entity: Entity, animator: Animator<Transform>, time: Duration) {
+    commands
+        .entity(entity)
+        .insert((animator, Animating(Timer::new(time, TimerMode::Once))));
+}
+
+/// Hops the pieces to their new tiles, hidden pieces are moved instantly.
+pub fn animate_moves(
+    mut commands: Commands,
+    speed: Res<AnimationSpeed>,
+    mut query: Query<
+        (Entity, &PiecePos, &mut Transform, &Visibility),
+        (Changed<PiecePos>, With<Piece>, Without<MapTile>),
+    >,
+) {
+    for (entity, pos, mut transform, visibility) in query.iter_mut() {
+        let (start, end) = (transform.translation, piece_translation(pos));
+        if visibility == Visibility::Hidden || start.distance(end) > MAX_HOP_DISTANCE {
+            transform.translation = end;
+            continue;
+        }
+        let top = start.lerp(end, 0.5) + Vec3::Y * HOP_HEIGHT;
+        let hop = speed.scale(HOP_TIME);
+        let tween = position_tween(hop, start, top).then(position_tween(hop, top, end));
+        animate(&mut commands, entity, Animator::new(tween), hop * 2);
+    }
+}
+
+/// Lunges the attacker towards the attacked tile and back.
+pub fn animate_attacks(
+    mut commands: Commands,
+    speed: Res<AnimationSpeed>,
+    mut events: EventReader<MeleeAttack>,
+    query: Query<(&PiecePos, &Visibility)>,
+) {
+    for attack in events.read() {
+        let Ok((pos, visibility)) = query.get(attack.attacker) else {
+            continue;
+        };
+        if visibility == Visibility::Hidden {
+            continue;
+        }
+        let start = piece_translation(pos);
+        let target = Vec3::new(attack.target.x as f32, 0.5, attack.target.y as f32);
+        let reach = start.lerp(target, LUNGE_REACH);
+        let lunge = speed.scale(LUNGE_TIME);
+        let tween = position_tween(lunge, start, reach).then(position_tween(lunge, reach, start));
+        animate(
+            &mut commands,
+            attack.attacker,
+            Animator::new(tween),
+            lunge * 2,
+        );
+    }
+}
+
+/// Drops the piece down the pit.
+pub fn animate_fall(
+    commands: &mut Commands,
+    speed: &AnimationSpeed,
+    entity: Entity,
+    transform: &Transform,
+) {
+    let start = transform.translation;
+    let fall = speed.scale(FALL_TIME);
+    let tween = Tween::new(
+        EaseFunction::QuadraticIn,
+        fall,
+        TransformPositionLens {
+            start,
+            end: start + Vec3::NEG_Y * 3.0,
+        },
+    );
+    animate(commands, entity, Animator::new(tween), fall);
+}
+
+/// Tints the pieces that lost health.
+pub fn flash_hits(
+    mut commands: Commands,
+    mut events: EventReader<PieceDamaged>,
+    mut query: Query<&mut Sprite3d>,
+) {
+    for event in events.read() {
+        let Ok(mut sprite) = query.get_mut(event.piece) else {
+            continue;
+        };
+        sprite.color = FLASH_COLOR;
+        commands
+            .entity(event.piece)
+            .insert(HitFlash(Timer::new(FLASH_TIME, TimerMode::Once)));
+    }
+}
+
+pub fn finish_animations(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut animating: Query<(Entity, &mut Animating)>,
+    mut flashes: Query<(Entity, &mut HitFlash, &mut Sprite3d)>,
+) {
+    for (entity, mut animating) in animating.iter_mut() {
+        if animating.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Animating>();
+        }
+    }
+    for (entity, mut flash, mut sprite) in flashes.iter_mut() {
+        if flash.0.tick(time.delta()).finished() {
+            sprite.color = Color::WHITE;
+            commands.entity(entity).remove::<HitFlash>();
+        }
+    }
+}
@@ -0,0 +1,242 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    platform::collections::HashSet,
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use super::{
+    components::CurrentBoard, renderer, state::BoardQuery, BoardPieceToGen, PiecePos, PlayerControl,
+};
+use crate::{
+    gfx::{GameBillboards, BILLBOARD_TINT},
+    states::MainGameState,
+    vectors::Vector2Int,
+};
+
+/// Width and height of the chunk in tiles.
+pub const CHUNK_SIZE: i32 = 8;
+const ATLAS_COLUMNS: usize = 49;
+const ATLAS_ROWS: usize = 22;
+const FLOOR_SPRITES: [usize; 6] = [685, 734, 774, 775, 830, 831];
+
+/// Static floors and walls of the board area baked into one mesh.
+/// Tiles the player cannot see are left transparent, see [`ChunkTiles`].
+#[derive(Component)]
+#[require(StateScoped::<MainGameState>(MainGameState::Game))]
+pub struct MapChunk(pub Vector2Int);
+
+/// Tile each quad of the chunk mesh belongs to, in the order of the quads.
+#[derive(Component)]
+pub struct ChunkTiles(Vec<Vector2Int>);
+
+/// Vertex color of the quads, the hidden ones are cut out by the alpha mask.
+fn fog_color(visible: bool) -> [f32; 4] {
+    let alpha = if visible { 1.0 } else { 0.0 };
+    [
+        BILLBOARD_TINT.red,
+        BILLBOARD_TINT.green,
+        BILLBOARD_TINT.blue,
+        alpha,
+    ]
+}
+
+pub fn chunk_of(pos: Vector2Int) -> Vector2Int {
+    Vector2Int::new(pos.x.div_euclid(CHUNK_SIZE), pos.y.div_euclid(CHUNK_SIZE))
+}
+
+fn chunk_tiles(chunk: Vector2Int) -> impl Iterator<Item = Vector2Int> {
+    (0..CHUNK_SIZE).flat_map(move |x| {
+        (0..CHUNK_SIZE)
+            .map(move |y| Vector2Int::new(chunk.x * CHUNK_SIZE + x, chunk.y * CHUNK_SIZE + y))
+    })
+}
+
+/// Floor sprite depends only on the position, so rebuilt chunks look the same.
+fn floor_sprite_index(pos: Vector2Int) -> usize {
+    let seed = ((pos.x as u64) << 32) ^ (pos.y as u32 as u64);
+    *FLOOR_SPRITES
+        .choose(&mut StdRng::seed_from_u64(seed))
+        .unwrap()
+}
+
+#[derive(Default)]
+struct ChunkMeshBuilder {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+    tiles: Vec<Vector2Int>,
+}
+
+impl ChunkMeshBuilder {
+    /// Adds the unit quad facing +Z in its local space, like the sprite billboards.
+    /// The quad is visible only together with the `tile`.
+    fn add_quad(&mut self, transform: Transform, index: usize, tile: Vector2Int) {
+        let start = self.positions.len() as u32;
+        let normal = transform.rotation * Vec3::Z;
+        let (col, row) = (
+            (index % ATLAS_COLUMNS) as f32,
+            (index / ATLAS_COLUMNS) as f32,
+        );
+        let (columns, rows) = (ATLAS_COLUMNS as f32, ATLAS_ROWS as f32);
+        for (x, y, u, v) in [
+            (-0.5, -0.5, col, row + 1.0),
+            (0.5, -0.5, col + 1.0, row + 1.0),
+            (0.5, 0.5, col + 1.0, row),
+            (-0.5, 0.5, col, row),
+        ] {
+            self.positions
+                .push(transform.transform_point(Vec3::new(x, y, 0.0)).into());
+            self.normals.push(normal.into());
+            self.uvs.push([u / columns, v / rows]);
+        }
+        self.indices
+            .extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+        self.tiles.push(tile);
+    }
+
+    /// The mesh starts hidden, the colors are set by [`update_chunk_visibility`].
+    fn build(self) -> Option<(Mesh, ChunkTiles)> {
+        if self.indices.is_empty() {
+            return None;
+        }
+        let colors = vec![fog_color(false); self.positions.len()];
+        // the colors change with the fog, the mesh stays in the main world
+        let mesh = Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, self.positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+        .with_inserted_indices(Indices::U32(self.indices));
+        Some((mesh, ChunkTiles(self.tiles)))
+    }
+}
+
+/// Builds the mesh of the floors and walls in the chunk, None when there is nothing to draw.
+fn chunk_mesh(board: &CurrentBoard, chunk: Vector2Int) -> Option<(Mesh, ChunkTiles)> {
+    let mut builder = ChunkMeshBuilder::default();
+    for pos in chunk_tiles(chunk) {
+        let Some(piece) = BoardPieceToGen::from_pos(pos, board) else {
+            continue;
+        };
+        for (transform, index) in piece.get_walls_transforms() {
+            builder.add_quad(transform, index, pos);
+        }
+        if !piece.tile_type.has_floor() {
+            continue;
+        }
+        let index = renderer::hazard_sprite_index(&piece.tile_type)
+            .unwrap_or_else(|| floor_sprite_index(pos));
+        builder.add_quad(
+            Transform::from_xyz(pos.x as f32, 0.0, pos.y as f32)
+                .with_rotation(Quat::from_rotation_x(-std::f32::consts::PI / 2.0)),
+            index,
+            pos,
+        );
+    }
+    builder.build()
+}
+
+/// Spawns the meshes of the given chunks, the old ones have to be despawned first.
+pub fn spawn_chunks(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    billboards: &GameBillboards,
+    board: &CurrentBoard,
+    chunks: impl IntoIterator<Item = Vector2Int>,
+) {
+    for chunk in chunks {
+        let Some((mesh, tiles)) = chunk_mesh(board, chunk) else {
+            continue;
+        };
+        commands.spawn((
+            Mesh3d(meshes.add(mesh)),
+            MeshMaterial3d(billboards.chunk_mat.clone()),
+            Transform::default(),
+            Visibility::Hidden,
+            Name::new(format!("Chunk{}x{}", chunk.x, chunk.y)),
+            MapChunk(chunk),
+            tiles,
+        ));
+    }
+}
+
+/// All chunks with at least one tile of the board.
+pub fn board_chunks(board: &CurrentBoard) -> Vec<Vector2Int> {
+    let mut chunks: Vec<Vector2Int> = board.tiles.keys().map(|pos| chunk_of(*pos)).collect();
+    chunks.sort_by_key(|c| (c.x, c.y));
+    chunks.dedup();
+    chunks
+}
+
+/// Shows the tiles of the chunks the player can see, the rest of the geometry stays hidden
+/// just like the tiles and pieces rendered on their own.
+pub fn update_chunk_visibility(
+    player_query: Query<Ref<PiecePos>, With<PlayerControl>>,
+    mut q: Query<(&mut Visibility, &Mesh3d, &MapChunk, &ChunkTiles)>,
+    rebuilt: Query<(), Added<MapChunk>>,
+    board: BoardQuery,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let Ok(player_pos) = player_query.single() else {
+        return;
+    };
+    if !player_pos.is_changed() && !board.is_changed() && rebuilt.is_empty() {
+        return;
+    }
+    let player = player_pos.0;
+    for (mut vis, mesh, chunk, tiles) in q.iter_mut() {
+        let seen: HashSet<Vector2Int> = chunk_tiles(chunk.0)
+            .filter(|pos| renderer::visibility(&board, player, *pos) == Visibility::Inherited)
+            .collect();
+        vis.set_if_neq(if seen.is_empty() {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        });
+        let colors: Vec<[f32; 4]> = tiles
+            .0
+            .iter()
+            .flat_map(|tile| [fog_color(seen.contains(tile)); 4])
+            .collect();
+        // taking the mesh as mutable uploads it again, skip the chunks that stay the same
+        let unchanged = matches!(
+            meshes.get(&mesh.0).and_then(|m| m.attribute(Mesh::ATTRIBUTE_COLOR)),
+            Some(VertexAttributeValues::Float32x4(old)) if *old == colors
+        );
+        if unchanged {
+            continue;
+        }
+        if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quads_belong_to_their_tiles() {
+        // two rooms in the same chunk, split by a wall
+        let board = CurrentBoard::parse("fP f\nff f").unwrap();
+        let (mesh, tiles) = chunk_mesh(&board, Vector2Int::new(0, 0)).unwrap();
+        let colors = match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+            Some(VertexAttributeValues::Float32x4(colors)) => colors,
+            _ => panic!("no vertex colors"),
+        };
+        assert_eq!(colors.len(), tiles.0.len() * 4);
+        assert!(colors.iter().all(|c| c[3] == 0.0), "chunk starts hidden");
+        let right_room = Vector2Int::new(3, 0);
+        let quads = tiles.0.iter().filter(|t| **t == right_room).count();
+        // the floor and both sides of the walls on the left, top and right
+        assert_eq!(quads, 7);
+        assert!(tiles.0.iter().all(|t| board.tiles.contains_key(t)));
+    }
+}
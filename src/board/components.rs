@@ -1,29 +1,85 @@
-use bevy::{platform::collections::HashMap, prelude::*};
+use bevy::{
+    ecs::{
+        component::{ComponentHook, HookContext, Immutable, Mutable, StorageType},
+        world::DeferredWorld,
+    },
+    platform::collections::HashMap,
+    prelude::*,
+};
 
+pub use crate::rules::{Item, Piece, TileType, Trap, TrapKind};
 use crate::{states::MainGameState, vectors::Vector2Int};
 
-#[derive(Component, Reflect, Default, PartialEq, Eq, PartialOrd, Ord, Clone)]
-pub enum TileType {
-    #[default]
-    None,
-    BaseFloor,
-    Pit,
+// the rules keep their types free of the ECS, they become components only here
+impl Component for Piece {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+    type Mutability = Immutable;
 }
 
-#[derive(Component, Reflect, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
-#[component(immutable)]
-pub enum Piece {
-    Player,
-    Enemy,
+impl Component for Trap {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+    type Mutability = Mutable;
+
+    fn on_insert() -> Option<ComponentHook> {
+        Some(index_trap)
+    }
+
+    fn on_replace() -> Option<ComponentHook> {
+        Some(unindex_trap)
+    }
+}
+
+/// Keys carried by the piece, each opens a single locked door.
+#[derive(Component, Reflect, Default, Deref, DerefMut)]
+pub struct Keys(pub u32);
+
+/// Bombs carried by the piece, each blows up a single cracked wall.
+#[derive(Component, Reflect, Default, Deref, DerefMut)]
+pub struct Bombs(pub u32);
+
+/// Tile of the [`CurrentBoard`] changed during the game, the tile and its neighbours need
+/// to be rendered again.
+#[derive(Event)]
+pub struct TileChanged(pub Vector2Int);
+
+/// The piece on the tile lost health, `health` is what is left of it.
+#[derive(Event)]
+pub struct PieceDamaged {
+    pub piece: Entity,
+    pub pos: Vector2Int,
+    pub amount: u32,
+    pub health: u32,
+}
+
+/// The piece attacked the tile in melee.
+#[derive(Event)]
+pub struct MeleeAttack {
+    pub attacker: Entity,
+    pub target: Vector2Int,
 }
 
+/// Monster woken up by the alarm, it acts no matter how far away the player is.
+#[derive(Component, Reflect)]
+pub struct Awake;
+
+/// Turns left until the piece stops burning, it takes damage in each of them.
+#[derive(Component, Reflect, Default, Deref, DerefMut)]
+pub struct Burning(pub u32);
+
+/// Piece is still being animated, the turn waits for it when [`crate::states::WaitForAnimations`] is set.
+#[derive(Component)]
+pub struct Animating(pub Timer);
+
 #[derive(Component)]
 #[require(StateScoped::<MainGameState>(MainGameState::Game))]
 pub struct GameObject;
 
+/// Position on the board. It is immutable so every move goes through the insert hooks
+/// that keep the [`Occupancy`] index up to date.
 #[derive(
     Component, Reflect, PartialEq, Default, Eq, PartialOrd, Ord, Clone, Copy, Deref, DerefMut,
 )]
+#[component(immutable, on_insert = occupy_tile, on_replace = free_tile)]
 pub struct PiecePos(pub Vector2Int);
 
 impl From<PiecePos> for Transform {
@@ -39,17 +95,120 @@ impl From<&PiecePos> for Transform {
 
 #[derive(Component, Default, Reflect)]
 #[require(PiecePos)]
+#[component(on_insert = occupy_tile, on_replace = free_tile)]
 pub struct Occupier;
 
+/// Index of the tiles taken by [`Occupier`]s, for the O(1) lookups in actions and AI.
+#[derive(Default, Resource, Reflect)]
+pub struct Occupancy(HashMap<Vector2Int, Entity>);
+
+impl Occupancy {
+    pub fn get(&self, pos: Vector2Int) -> Option<Entity> {
+        self.0.get(&pos).copied()
+    }
+
+    fn insert(&mut self, pos: Vector2Int, entity: Entity) {
+        let previous = self.0.insert(pos, entity);
+        debug_assert!(
+            previous.is_none_or(|e| e == entity),
+            "Two occupiers on the tile {:?}: {:?} and {:?}",
+            pos,
+            previous,
+            entity
+        );
+    }
+
+    fn remove(&mut self, pos: Vector2Int, entity: Entity) {
+        if self.get(pos) == Some(entity) {
+            self.0.remove(&pos);
+        }
+    }
+}
+
+fn occupy_tile(mut world: DeferredWorld, context: HookContext) {
+    let entity = world.entity(context.entity);
+    let (Some(pos), true) = (entity.get::<PiecePos>(), entity.contains::<Occupier>()) else {
+        return;
+    };
+    let pos = pos.0;
+    if let Some(mut occupancy) = world.get_resource_mut::<Occupancy>() {
+        occupancy.insert(pos, context.entity);
+    }
+}
+
+fn free_tile(mut world: DeferredWorld, context: HookContext) {
+    let entity = world.entity(context.entity);
+    let (Some(pos), true) = (entity.get::<PiecePos>(), entity.contains::<Occupier>()) else {
+        return;
+    };
+    let pos = pos.0;
+    if let Some(mut occupancy) = world.get_resource_mut::<Occupancy>() {
+        occupancy.remove(pos, context.entity);
+    }
+}
+
+/// Index of the tiles with a [`Trap`], traps are spawned together with their [`PiecePos`]
+/// and never move.
+#[derive(Default, Resource, Reflect)]
+pub struct TrapIndex(HashMap<Vector2Int, Entity>);
+
+impl TrapIndex {
+    pub fn get(&self, pos: Vector2Int) -> Option<Entity> {
+        self.0.get(&pos).copied()
+    }
+}
+
+fn index_trap(mut world: DeferredWorld, context: HookContext) {
+    let Some(pos) = world.get::<PiecePos>(context.entity).map(|p| p.0) else {
+        return;
+    };
+    if let Some(mut index) = world.get_resource_mut::<TrapIndex>() {
+        index.0.insert(pos, context.entity);
+    }
+}
+
+fn unindex_trap(mut world: DeferredWorld, context: HookContext) {
+    let Some(pos) = world.get::<PiecePos>(context.entity).map(|p| p.0) else {
+        return;
+    };
+    if let Some(mut index) = world.get_resource_mut::<TrapIndex>() {
+        if index.get(pos) == Some(context.entity) {
+            index.0.remove(&pos);
+        }
+    }
+}
+
 #[derive(Component)]
 #[require(StateScoped::<MainGameState>(MainGameState::Game))]
 #[require(PiecePos)]
 pub struct MapTile;
 
-#[derive(Default, Resource, Reflect)]
+/// Floor of the dungeon the player is on, starting from 1.
+#[derive(Resource, Reflect, Clone, Copy, Deref, DerefMut)]
+pub struct Depth(pub u32);
+
+impl Default for Depth {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// Seed used to generate the board, the same seed always gives the same run.
+#[derive(Resource, Reflect, Clone, Copy, Deref)]
+pub struct RunSeed(pub u64);
+
+impl Default for RunSeed {
+    fn default() -> Self {
+        Self(rand::random())
+    }
+}
+
+#[derive(Default, Resource, Reflect, Clone)]
 pub struct CurrentBoard {
     pub tiles: HashMap<Vector2Int, TileType>,
     pub spawn_points: HashMap<Vector2Int, Piece>,
+    pub items: HashMap<Vector2Int, Item>,
+    pub traps: HashMap<Vector2Int, TrapKind>,
 }
 
 impl CurrentBoard {
@@ -57,7 +216,8 @@ impl CurrentBoard {
         self.tiles.get(&Vector2Int { x, y })
     }
 
-    pub fn print(&self) {
+    /// Board in the format of the level files, starting from `0x0`.
+    pub fn to_text(&self) -> String {
         let max_x = self.tiles.iter().map(|t| t.0.x).max().unwrap();
         let max_y = self.tiles.iter().map(|t| t.0.y).max().unwrap();
 
@@ -66,23 +226,19 @@ impl CurrentBoard {
             lines.push(vec!['*'; max_x as usize + 1]);
         }
         for (pos, tile_type) in self.tiles.iter() {
-            lines[pos.y as usize][pos.x as usize] = match (tile_type, self.spawn_points.get(pos)) {
-                (TileType::None, _) => '#',
-                (TileType::BaseFloor, spawn) => match spawn {
-                    Some(piece) => {
-                        if piece == &Piece::Enemy {
-                            'E'
-                        } else {
-                            'P'
-                        }
-                    }
-                    None => 'f',
-                },
-                (TileType::Pit, _) => 'p',
-            };
+            lines[pos.y as usize][pos.x as usize] =
+                super::level::glyph(tile_type, self.spawn_points.get(pos), self.items.get(pos));
         }
-        for line in lines {
-            info!("{}", line.iter().collect::<String>());
+        lines
+            .iter()
+            .map(|line| line.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn print(&self) {
+        for line in self.to_text().lines() {
+            info!("{}", line);
         }
     }
 }
@@ -92,6 +248,10 @@ pub struct Health {
     pub value: u32,
 }
 
+/// Health the piece started with.
+#[derive(Component, Reflect, Deref)]
+pub struct MaxHealth(pub u32);
+
 /// melee attack behaviour for the npcs
 #[derive(Component, Reflect)]
 pub struct Melee {
@@ -117,7 +277,7 @@ pub struct Flying;
 impl Default for AiControl {
     fn default() -> Self {
         Self {
-            max_distance_to_player: 5,
+            max_distance_to_player: crate::rules::ENEMY_PATH_DISTANCE,
         }
     }
 }
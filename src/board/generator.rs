@@ -1,13 +1,43 @@
 use super::components::*;
 use super::components::{CurrentBoard, TileType};
-use crate::{dungeon::*, states::ActorTurn, vectors::Vector2Int};
-use bevy::platform::collections::HashMap;
+use super::vaults;
+use crate::{
+    dungeon::*,
+    rules,
+    states::{ActorTurn, MainGameState},
+    vectors::Vector2Int,
+};
+use bevy::platform::collections::{HashMap, HashSet};
 use bevy::prelude::*;
-use rand::Rng;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::collections::VecDeque;
 
-pub fn create_map(mut commands: Commands) {
-    info!("Start world generate");
+/// Chance of a door on the corridor tile next to the room.
+const DOOR_CHANCE: f64 = 0.5;
+const LOCKED_DOOR_CHANCE: f64 = 0.25;
+/// Amount of the thin walls turned into the cracked ones.
+const CRACKED_WALLS: std::ops::RangeInclusive<usize> = 1..=3;
+/// Chance of a room having some tiles of one of the hazards.
+const HAZARD_CHANCE: f64 = 0.4;
+/// Chance of a room having a hidden trap.
+const TRAP_CHANCE: f64 = 0.3;
+const TRAPS: [TrapKind; 4] = [
+    TrapKind::PressurePlate,
+    TrapKind::Dart,
+    TrapKind::Teleport,
+    TrapKind::Alarm,
+];
+const HAZARDS: [TileType; 4] = [
+    TileType::Spikes,
+    TileType::Lava,
+    TileType::Water,
+    TileType::Ice,
+];
 
+pub fn create_map(mut commands: Commands, seed: Res<RunSeed>) {
+    info!("Start world generate, seed: {}", **seed);
+
+    let mut rng = StdRng::seed_from_u64(**seed);
     let mut dungeon = Dungeon::new(3);
 
     for idx in 0..6 {
@@ -17,31 +47,38 @@ pub fn create_map(mut commands: Commands) {
         };
         dungeon.add_area(Area::new(tun))
     }
-    dungeon.generate();
+    dungeon.generate(&mut rng);
     let mut tiles: HashMap<Vector2Int, TileType> = dungeon
         .to_tiles()
         .iter()
         .map(|p| (*p, TileType::BaseFloor))
         .collect();
     let mut spawn_points = HashMap::new();
+    let mut traps = HashMap::new();
     let mut first_room = true;
     for area in dungeon.areas.iter() {
         for room in area.rooms.iter() {
             if first_room {
-                spawn_points.insert(room.random_point_without_walls(), Piece::Player);
+                spawn_points.insert(room.random_point_without_walls(&mut rng), Piece::Player);
                 first_room = false;
                 continue;
             }
-            let mut rng = rand::thread_rng();
             for _ in 0..rng.gen_range(0..4) {
-                let point = room.random_point_without_walls();
+                let point = room.random_point_without_walls(&mut rng);
                 tiles.entry(point).and_modify(|e| *e = TileType::Pit);
             }
+            if rng.gen_bool(HAZARD_CHANCE) {
+                let hazard = &HAZARDS[rng.gen_range(0..HAZARDS.len())];
+                for _ in 0..rng.gen_range(1..=4) {
+                    let point = room.random_point_without_walls(&mut rng);
+                    tiles.entry(point).and_modify(|e| *e = hazard.clone());
+                }
+            }
 
             let enemies_amount = rng.gen_range(1..=4);
             for _ in 0..enemies_amount {
                 for _ in 0..5 {
-                    let random_point = room.random_point();
+                    let random_point = room.random_point(&mut rng);
 
                     if tiles[&random_point] == TileType::BaseFloor
                         && !spawn_points.contains_key(&random_point)
@@ -51,24 +88,176 @@ pub fn create_map(mut commands: Commands) {
                     }
                 }
             }
+            if rng.gen_bool(TRAP_CHANCE) {
+                let point = room.random_point_without_walls(&mut rng);
+                if tiles[&point] == TileType::BaseFloor && !spawn_points.contains_key(&point) {
+                    traps.insert(point, TRAPS[rng.gen_range(0..TRAPS.len())]);
+                }
+            }
         }
     }
-    let new_board = CurrentBoard {
+    let mut new_board = CurrentBoard {
         tiles,
         spawn_points,
+        traps,
+        ..default()
     };
+    vaults::stamp_vaults(&mut new_board, rng.gen_range(1..=2), &mut rng);
+    let room_tiles = dungeon
+        .areas
+        .iter()
+        .flat_map(|a| a.rooms.iter().flat_map(|r| r.to_tiles()))
+        .collect();
+    place_doors(&mut new_board, &room_tiles, &mut rng);
+    place_cracked_walls(&mut new_board, &mut rng);
+    for (item, tile) in [
+        (Item::Key, TileType::LockedDoor),
+        (Item::Bomb, TileType::CrackedWall),
+    ] {
+        let amount = new_board.tiles.values().filter(|t| **t == tile).count();
+        place_items(&mut new_board, item, amount, &mut rng);
+    }
 
     new_board.print();
     commands.insert_resource(new_board);
 }
 
+/// Puts doors on the corridor tiles leading into the rooms.
+fn place_doors(board: &mut CurrentBoard, room_tiles: &HashSet<Vector2Int>, rng: &mut StdRng) {
+    let mut doorways: Vec<Vector2Int> = board
+        .tiles
+        .iter()
+        .filter(|(pos, tile)| {
+            **tile == TileType::BaseFloor
+                && !room_tiles.contains(*pos)
+                && !board.spawn_points.contains_key(*pos)
+        })
+        .map(|(pos, _)| *pos)
+        .filter(|pos| {
+            [Vector2Int::UP, Vector2Int::RIGHT]
+                .iter()
+                .any(|dir| is_doorway(board, room_tiles, *pos, *dir))
+        })
+        .collect();
+    // the board is a hash map, keep the order the same for the seed
+    doorways.sort();
+    for pos in doorways {
+        if !rng.gen_bool(DOOR_CHANCE) {
+            continue;
+        }
+        let door = if rng.gen_bool(LOCKED_DOOR_CHANCE) {
+            TileType::LockedDoor
+        } else {
+            TileType::Door { open: false }
+        };
+        board.tiles.insert(pos, door);
+    }
+}
+
+/// Walls on both sides and a room on one of the ends along the `dir` axis.
+fn is_doorway(
+    board: &CurrentBoard,
+    room_tiles: &HashSet<Vector2Int>,
+    pos: Vector2Int,
+    dir: Vector2Int,
+) -> bool {
+    let side = Vector2Int::new(dir.y, dir.x);
+    let is_floor = |p: Vector2Int| board.tiles.get(&p) == Some(&TileType::BaseFloor);
+    let is_wall = |p: Vector2Int| !board.tiles.contains_key(&p);
+    is_wall(pos + side)
+        && is_wall(pos - side)
+        && is_floor(pos + dir)
+        && is_floor(pos - dir)
+        && (room_tiles.contains(&(pos + dir)) || room_tiles.contains(&(pos - dir)))
+}
+
+/// Turns some of the thin walls between two floor tiles into cracked walls.
+fn place_cracked_walls(board: &mut CurrentBoard, rng: &mut StdRng) {
+    let tiles = &board.tiles;
+    let is_floor = |p: Vector2Int| tiles.get(&p) == Some(&TileType::BaseFloor);
+    let mut walls: Vec<Vector2Int> = tiles
+        .keys()
+        .filter(|pos| is_floor(**pos))
+        .flat_map(|pos| {
+            [Vector2Int::UP, Vector2Int::RIGHT]
+                .into_iter()
+                .filter(move |dir| {
+                    !tiles.contains_key(&(*pos + *dir)) && is_floor(*pos + *dir + *dir)
+                })
+                .map(move |dir| *pos + dir)
+        })
+        .collect();
+    // the board is a hash map, keep the order the same for the seed
+    walls.sort();
+    walls.dedup();
+    let amount = rng.gen_range(CRACKED_WALLS);
+    for pos in walls.choose_multiple(rng, amount) {
+        board.tiles.insert(*pos, TileType::CrackedWall);
+    }
+}
+
+/// Items placed where the player can get without passing locked doors and cracked walls.
+fn place_items(board: &mut CurrentBoard, item: Item, amount: usize, rng: &mut StdRng) {
+    let Some(start) = board
+        .spawn_points
+        .iter()
+        .find(|(_, piece)| **piece == Piece::Player)
+        .map(|(pos, _)| *pos)
+    else {
+        return;
+    };
+    let mut reachable = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(pos) = queue.pop_front() {
+        for dir in [
+            Vector2Int::UP,
+            Vector2Int::DOWN,
+            Vector2Int::LEFT,
+            Vector2Int::RIGHT,
+        ] {
+            let next = pos + dir;
+            let passable = matches!(
+                board.tiles.get(&next),
+                Some(TileType::BaseFloor | TileType::Door { .. })
+            );
+            if passable && reachable.insert(next) {
+                queue.push_back(next);
+            }
+        }
+    }
+    let mut free: Vec<Vector2Int> = reachable
+        .into_iter()
+        .filter(|pos| {
+            board.tiles.get(pos) == Some(&TileType::BaseFloor)
+                && !board.spawn_points.contains_key(pos)
+                && !board.items.contains_key(pos)
+                && !board.traps.contains_key(pos)
+        })
+        .collect();
+    free.sort();
+    for _ in 0..amount {
+        if free.is_empty() {
+            return;
+        }
+        let pos = free.swap_remove(rng.gen_range(0..free.len()));
+        board.items.insert(pos, item.clone());
+    }
+}
+
 pub fn spawn_points(mut commands: Commands, board: Res<CurrentBoard>) {
+    for (point, kind) in board.traps.iter() {
+        commands.spawn((
+            Trap::new(*kind),
+            PiecePos(*point),
+            StateScoped(MainGameState::Game),
+        ));
+    }
     for (point, piece) in board.spawn_points.iter() {
         let id = commands
             .spawn((
                 piece.clone(),
                 Occupier,
-                ActorTurn(if piece == &Piece::Player { 0 } else { 1 }),
+                ActorTurn(rules::first_turn(piece)),
                 PiecePos(*point),
                 GameObject,
             ))
@@ -77,15 +266,27 @@ pub fn spawn_points(mut commands: Commands, board: Res<CurrentBoard>) {
             Piece::Player => {
                 commands.entity(id).insert((
                     PlayerControl,
-                    Health { value: 3 },
-                    Melee { damage: 1 },
+                    Health {
+                        value: rules::PLAYER_HEALTH,
+                    },
+                    MaxHealth(rules::PLAYER_HEALTH),
+                    Melee {
+                        damage: rules::MELEE_DAMAGE,
+                    },
+                    Keys::default(),
+                    Bombs::default(),
                 ));
             }
             Piece::Enemy => {
                 commands.entity(id).insert((
                     AiControl::default(),
-                    Health { value: 1 },
-                    Melee { damage: 1 },
+                    Health {
+                        value: rules::ENEMY_HEALTH,
+                    },
+                    MaxHealth(rules::ENEMY_HEALTH),
+                    Melee {
+                        damage: rules::MELEE_DAMAGE,
+                    },
                 ));
             }
         }
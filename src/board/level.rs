@@ -0,0 +1,257 @@
+//! Hand-made boards stored in `assets/levels/*.txt`, in the format written by [`CurrentBoard::to_text`].
+//!
+//! Every line is a row of the board starting from `y = 0`, every glyph is a tile.
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    platform::collections::HashMap,
+    prelude::*,
+};
+
+use super::{components::*, generator};
+use crate::{states::MainGameState, vectors::Vector2Int};
+
+/// Tile with the piece spawned and the item lying on it.
+pub type Cell = (TileType, Option<Piece>, Option<Item>);
+
+/// Glyphs of the cells, the first one is used when printing.
+const GLYPHS: [(char, Cell); 17] = [
+    ('#', (TileType::None, None, None)),
+    ('f', (TileType::BaseFloor, None, None)),
+    ('.', (TileType::BaseFloor, None, None)),
+    ('p', (TileType::Pit, None, None)),
+    ('d', (TileType::Door { open: false }, None, None)),
+    ('o', (TileType::Door { open: true }, None, None)),
+    ('l', (TileType::LockedDoor, None, None)),
+    ('^', (TileType::Spikes, None, None)),
+    ('%', (TileType::Lava, None, None)),
+    ('~', (TileType::Water, None, None)),
+    ('_', (TileType::Ice, None, None)),
+    ('c', (TileType::CrackedWall, None, None)),
+    ('k', (TileType::BaseFloor, None, Some(Item::Key))),
+    ('b', (TileType::BaseFloor, None, Some(Item::Bomb))),
+    ('E', (TileType::BaseFloor, Some(Piece::Enemy), None)),
+    ('P', (TileType::BaseFloor, Some(Piece::Player), None)),
+    ('@', (TileType::BaseFloor, Some(Piece::Player), None)),
+];
+/// Glyphs of the places outside of the board.
+const EMPTY: [char; 2] = ['*', ' '];
+
+pub fn glyph(tile: &TileType, spawn: Option<&Piece>, item: Option<&Item>) -> char {
+    let find = |spawn: Option<&Piece>, item: Option<&Item>| {
+        GLYPHS
+            .iter()
+            .find(|(_, (t, p, i))| t == tile && p.as_ref() == spawn && i.as_ref() == item)
+    };
+    find(spawn, item)
+        .or_else(|| find(spawn, None))
+        .or_else(|| find(None, None))
+        .map_or('?', |(c, _)| *c)
+}
+
+/// Cell of the glyph, `None` for the places outside of the board.
+pub fn parse_glyph(c: char) -> Result<Option<Cell>, String> {
+    if EMPTY.contains(&c) {
+        return Ok(None);
+    }
+    GLYPHS
+        .iter()
+        .find(|(g, _)| *g == c)
+        .map(|(_, cell)| Some(cell.clone()))
+        .ok_or_else(|| format!("Unknown glyph '{}'", c))
+}
+
+impl CurrentBoard {
+    /// Builds the board from the level text, the board needs exactly one player.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut tiles = HashMap::new();
+        let mut spawn_points = HashMap::new();
+        let mut items = HashMap::new();
+        for (y, line) in text.lines().enumerate() {
+            for (x, c) in line.trim_end_matches('\r').chars().enumerate() {
+                let Some((tile, spawn, item)) =
+                    parse_glyph(c).map_err(|e| format!("{} at {}x{}", e, x, y))?
+                else {
+                    continue;
+                };
+                let pos = Vector2Int::new(x as i32, y as i32);
+                tiles.insert(pos, tile);
+                if let Some(piece) = spawn {
+                    spawn_points.insert(pos, piece);
+                }
+                if let Some(item) = item {
+                    items.insert(pos, item);
+                }
+            }
+        }
+        let players = spawn_points
+            .values()
+            .filter(|p| *p == &Piece::Player)
+            .count();
+        if players != 1 {
+            return Err(format!("Level needs one player, found {}", players));
+        }
+        Ok(Self {
+            tiles,
+            spawn_points,
+            items,
+            ..default()
+        })
+    }
+}
+
+#[derive(Asset, TypePath, Deref)]
+pub struct Level(pub CurrentBoard);
+
+/// Level played instead of the generated board.
+#[derive(Resource, Default)]
+pub struct SelectedLevel(pub Option<Handle<Level>>);
+
+#[derive(Default)]
+struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = String;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Level, String> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|e| e.to_string())?;
+        let text = String::from_utf8(bytes).map_err(|e| e.to_string())?;
+        CurrentBoard::parse(&text).map(Level)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["txt"]
+    }
+}
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Level>()
+            .init_asset_loader::<LevelLoader>()
+            .init_resource::<SelectedLevel>()
+            .add_systems(
+                OnEnter(MainGameState::Game),
+                use_selected_level.before(generator::create_map),
+            );
+
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Startup, select_level_from_args);
+    }
+}
+
+/// `--level <name>` plays `assets/levels/<name>.txt`.
+///
+/// The file is read right away, an asset loaded in the background could miss the start of the game.
+#[cfg(not(target_arch = "wasm32"))]
+fn select_level_from_args(mut selected: ResMut<SelectedLevel>, mut levels: ResMut<Assets<Level>>) {
+    use bevy::asset::io::file::FileAssetReader;
+
+    let args: Vec<String> = std::env::args().collect();
+    let Some(name) = args
+        .iter()
+        .position(|a| a == "--level")
+        .and_then(|i| args.get(i + 1))
+    else {
+        return;
+    };
+    let path = FileAssetReader::get_base_path()
+        .join("assets/levels")
+        .join(format!("{}.txt", name));
+    let board = std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| CurrentBoard::parse(&text));
+    match board {
+        Ok(board) => {
+            info!("Level {:?} selected", path);
+            selected.0 = Some(levels.add(Level(board)));
+        }
+        Err(e) => error!("Level {:?} cannot be played: {}", path, e),
+    }
+}
+
+fn use_selected_level(
+    mut commands: Commands,
+    selected: Res<SelectedLevel>,
+    levels: Res<Assets<Level>>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(handle) = &selected.0 else {
+        return;
+    };
+    match levels.get(handle) {
+        Some(level) => commands.insert_resource(level.0.clone()),
+        None => warn!(
+            "Level {:?} is not loaded ({:?}), generating the board",
+            handle.path(),
+            asset_server.load_state(handle)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_files() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/levels");
+        for entry in std::fs::read_dir(dir).unwrap().flatten() {
+            let path = entry.path();
+            let text = std::fs::read_to_string(&path).unwrap();
+            if let Err(e) = CurrentBoard::parse(&text) {
+                panic!("{:?}: {}", path, e);
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_glyph() {
+        let e = CurrentBoard::parse("fPf\nfxf").err();
+        assert_eq!(e.as_deref(), Some("Unknown glyph 'x' at 1x1"));
+    }
+
+    #[test]
+    fn level_without_player() {
+        assert!(CurrentBoard::parse("fff\nfEf").is_err());
+    }
+
+    #[test]
+    fn level_with_two_players() {
+        let e = CurrentBoard::parse("fPf\nf@f").err();
+        assert_eq!(e.as_deref(), Some("Level needs one player, found 2"));
+    }
+
+    #[test]
+    fn ragged_rows() {
+        let board = CurrentBoard::parse("fPfff\nf\r\n  ff").unwrap();
+        assert_eq!(board.tiles.len(), 8);
+        assert_eq!(board.get(4, 0), Some(&TileType::BaseFloor));
+        assert_eq!(board.get(1, 1), None);
+        assert_eq!(board.get(0, 2), None);
+        assert_eq!(board.get(3, 2), Some(&TileType::BaseFloor));
+    }
+
+    #[test]
+    fn printed_board_parses_back() {
+        let text = "#######\n#Pkbd*#\n#Eopl^#\n#%~_c.#";
+        let board = CurrentBoard::parse(text).unwrap();
+        let printed = board.to_text();
+        assert_eq!(printed, text.replace('.', "f"));
+        let again = CurrentBoard::parse(&printed).unwrap();
+        assert_eq!(again.tiles, board.tiles);
+        assert_eq!(again.spawn_points, board.spawn_points);
+        assert_eq!(again.items, board.items);
+    }
+}
@@ -1,8 +1,10 @@
 use std::time::Duration;
 
 use crate::{
+    consts,
     gfx::GameBillboards,
     lights::{LightPattern, Torch},
+    messages::{MessageCategory, MessageLog},
     states::{self, GameTurnSteps},
     vectors::Vector2Int,
     FaceCamera, ImageAssets,
@@ -10,11 +12,16 @@ use crate::{
 use bevy::{prelude::*, time::common_conditions::on_timer};
 use bevy_sprite3d::{Sprite3d, Sprite3dBillboard};
 use components::*;
-use rand::{prelude::SliceRandom, Rng}; // optional if you want movement controls
+use rand::Rng; // optional if you want movement controls
 
+pub mod animations;
+pub mod chunks;
 pub mod components;
 pub mod generator;
+pub mod level;
 pub mod renderer;
+pub mod state;
+pub mod vaults;
 
 pub struct BoardPlugin;
 
@@ -25,21 +32,38 @@ pub struct BoardPieceToGen {
 }
 
 impl BoardPieceToGen {
-    pub fn get_walls_transforms(&self) -> Vec<Transform> {
+    /// Wall billboards around the tile with their atlas indices.
+    pub fn get_walls_transforms(&self) -> Vec<(Transform, usize)> {
         match self.tile_type {
-            TileType::BaseFloor => self
+            // the door frame stands between the corridor walls
+            TileType::BaseFloor
+            | TileType::Door { .. }
+            | TileType::LockedDoor
+            | TileType::Spikes
+            | TileType::Lava
+            | TileType::Water
+            | TileType::Ice => self
                 .neighbours
                 .iter()
-                .filter(|e| e.1.eq(&TileType::None))
-                .flat_map(|e| [1.0, 0.0].iter().map(|i| self.transform(e.0, i + 0.499)))
+                .filter(|e| e.1.is_wall())
+                .flat_map(|e| {
+                    let index = renderer::wall_sprite_index(&e.1);
+                    [1.0, 0.0]
+                        .iter()
+                        .map(move |i| (self.transform(e.0, i + 0.499), index))
+                })
                 .collect(),
             TileType::Pit => self
                 .neighbours
                 .iter()
-                .filter(|e| e.1.eq(&TileType::BaseFloor))
-                .flat_map(|e| [-1.0, -2.0].iter().map(|i| self.transform(e.0, i + 0.499)))
+                .filter(|e| e.1.has_floor())
+                .flat_map(|e| {
+                    [-1.0, -2.0]
+                        .iter()
+                        .map(|i| (self.transform(e.0, i + 0.499), consts::WALL))
+                })
                 .collect(),
-            TileType::None => vec![],
+            TileType::None | TileType::CrackedWall => vec![],
         }
     }
     pub fn transform(&self, neighbour: BoardNeighbour, y_offset: f32) -> Transform {
@@ -52,7 +76,7 @@ impl BoardPieceToGen {
     }
     pub fn from_pos(pos: Vector2Int, board: &CurrentBoard) -> Option<BoardPieceToGen> {
         let tile = board.get(pos.x, pos.y)?;
-        if tile.eq(&TileType::None) {
+        if tile.is_wall() {
             return None;
         }
         let up = board
@@ -125,33 +149,85 @@ impl Plugin for BoardPlugin {
             .register_type::<Piece>()
             .register_type::<PiecePos>()
             .register_type::<Health>()
+            .register_type::<MaxHealth>()
             .register_type::<PlayerControl>()
             .register_type::<PlayerPiece>()
             .register_type::<AiControl>()
             .register_type::<Animation>()
             .register_type::<Melee>()
-            .add_systems(
-                Update,
-                materials_check.run_if(on_timer(Duration::from_secs(5))),
-            )
+            .register_type::<Occupancy>()
+            .register_type::<TrapIndex>()
+            .register_type::<RunSeed>()
+            .register_type::<Depth>()
+            .register_type::<Item>()
+            .register_type::<Keys>()
+            .register_type::<Bombs>()
+            .register_type::<Burning>()
+            .register_type::<Trap>()
+            .register_type::<Awake>()
+            .add_event::<TileChanged>()
+            .add_event::<MeleeAttack>()
+            .add_event::<PieceDamaged>()
+            .init_resource::<Occupancy>()
+            .init_resource::<TrapIndex>()
+            .init_resource::<RunSeed>()
+            .init_resource::<Depth>()
             .add_systems(
                 OnEnter(states::MainGameState::Game),
                 (
-                    generator::create_map,
+                    // a board inserted before the game starts is used as it is
+                    generator::create_map.run_if(not(resource_exists::<CurrentBoard>)),
                     generator::spawn_points,
-                    generate_world,
                     start_search_for_agents,
                 )
                     .chain(),
             )
+            .add_systems(
+                Update,
+                pick_up_items.run_if(in_state(states::MainGameState::Game)),
+            )
+            .add_systems(OnExit(states::MainGameState::Game), remove_map);
+    }
+}
+
+/// Visuals of the board, not needed to run the game rules.
+pub struct BoardRenderPlugin;
+
+impl Plugin for BoardRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<animations::AnimationSpeed>()
+            .init_resource::<animations::AnimationSpeed>()
+            .add_systems(
+                Update,
+                materials_check.run_if(on_timer(Duration::from_secs(5))),
+            )
+            .add_systems(
+                OnEnter(states::MainGameState::Game),
+                generate_world
+                    .after(generator::spawn_points)
+                    .before(start_search_for_agents),
+            )
             .add_systems(
                 Update,
                 (
                     animate_sprites,
                     renderer::spawn_piece_renderer,
                     renderer::update_piece,
+                    (
+                        animations::animate_moves,
+                        animations::animate_attacks,
+                        animations::flash_hits,
+                        animations::finish_animations,
+                    )
+                        .before(states::execute_pending_action),
                     renderer::dig_the_grave,
                     renderer::update_tile_visibility,
+                    chunks::update_chunk_visibility,
+                    renderer::sync_items,
+                    renderer::sync_traps,
+                    rebuild_changed_tiles
+                        .before(renderer::update_tile_visibility)
+                        .before(chunks::update_chunk_visibility),
                 )
                     .run_if(in_state(states::MainGameState::Game)),
             )
@@ -161,8 +237,7 @@ impl Plugin for BoardPlugin {
                     in_state(states::MainGameState::Game)
                         .and(on_timer(Duration::from_secs_f32(0.1))),
                 ),
-            )
-            .add_systems(OnExit(states::MainGameState::Game), remove_map);
+            );
     }
 }
 
@@ -175,8 +250,35 @@ fn start_search_for_agents(mut next: ResMut<NextState<GameTurnSteps>>) {
     next.set(GameTurnSteps::SearchForAgents);
 }
 
+fn pick_up_items(
+    mut q: Query<(&PiecePos, &mut Keys, &mut Bombs), Changed<PiecePos>>,
+    mut board: ResMut<CurrentBoard>,
+    mut log: ResMut<MessageLog>,
+    turn: Res<states::TurnCounter>,
+) {
+    for (pos, mut keys, mut bombs) in q.iter_mut() {
+        if !board.items.contains_key(&pos.0) {
+            continue;
+        }
+        let text = match board.items.remove(&pos.0) {
+            Some(Item::Key) => {
+                **keys += 1;
+                "You found a key"
+            }
+            Some(Item::Bomb) => {
+                **bombs += 1;
+                "You found a bomb"
+            }
+            None => continue,
+        };
+        log.push(**turn, MessageCategory::Item, text);
+        info!("Picked up an item at {:?}", pos.0);
+    }
+}
+
 fn generate_world(
     mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
     assets: Res<ImageAssets>,
     billboards: Res<GameBillboards>,
     board: Res<CurrentBoard>,
@@ -184,115 +286,179 @@ fn generate_world(
     use std::time::Instant;
 
     let start = Instant::now();
-    let map = &board.tiles;
-    // random floor tile
-    let options_f = [685, 734, 774, 775, 830, 831];
-    let floor = || *options_f.choose(&mut rand::thread_rng()).unwrap();
-    let floor_atlas_gen = || TextureAtlas {
-        layout: assets.layout.clone(),
-        index: floor(),
-    };
+    info!("World generate- floors");
+    chunks::spawn_chunks(
+        &mut commands,
+        &mut meshes,
+        &billboards,
+        &board,
+        chunks::board_chunks(&board),
+    );
+    for pos in board.tiles.keys() {
+        spawn_tile(&mut commands, &assets, &billboards, &board, *pos);
+    }
+    let duration = start.elapsed();
+    error!("World generation: {}", duration.as_micros());
+}
 
-    let wall_atlas = TextureAtlas {
-        layout: assets.layout.clone(),
-        index: 843,
+/// Changes the tile of the board, its entities are rebuilt by the renderer.
+pub fn change_tile(world: &mut World, pos: Vector2Int, tile: TileType) -> bool {
+    let Some(mut board) = world.get_resource_mut::<CurrentBoard>() else {
+        return false;
     };
-    let wall_sprite = Sprite3d::from(wall_atlas);
-
-    info!("World generate- floors");
-    for (pos, tile_type) in map.iter() {
-        let Some(surounding_elements) = BoardPieceToGen::from_pos(*pos, &board) else {
-            continue;
-        };
-        let walls: Vec<(
-            MeshMaterial3d<StandardMaterial>,
-            Sprite3dBillboard,
-            Sprite3d,
-            Transform,
-            MapTile,
-            PiecePos,
-        )> = surounding_elements
-            .get_walls_transforms()
-            .iter()
-            .map(|t| {
-                (
-                    MeshMaterial3d(billboards.billboard_mat.clone()),
-                    Sprite3dBillboard::new(billboards.billboard.clone()),
-                    wall_sprite.clone(),
-                    *t,
-                    crate::board::MapTile,
-                    PiecePos(*pos),
-                )
-            })
-            .collect();
-        commands.spawn_batch(walls);
-        let (x, y) = (pos.x as f32, pos.y as f32);
+    if board.tiles.get(&pos) == Some(&tile) {
+        return true;
+    }
+    board.tiles.insert(pos, tile);
+    world.send_event(TileChanged(pos));
+    true
+}
 
-        if tile_type == &TileType::Pit {
-            continue;
+/// Rebuilds the chunks and doors around the changed tiles, the rest of the board stays.
+fn rebuild_changed_tiles(
+    mut commands: Commands,
+    mut events: EventReader<TileChanged>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    assets: Res<ImageAssets>,
+    billboards: Res<GameBillboards>,
+    board: Res<CurrentBoard>,
+    tiles: Query<(Entity, &PiecePos), (With<MapTile>, Without<renderer::TrapSprite>)>,
+    map_chunks: Query<(Entity, &chunks::MapChunk)>,
+) {
+    let mut affected = Vec::new();
+    for TileChanged(pos) in events.read() {
+        for dir in [
+            Vector2Int::default(),
+            Vector2Int::UP,
+            Vector2Int::DOWN,
+            Vector2Int::LEFT,
+            Vector2Int::RIGHT,
+        ] {
+            if !affected.contains(&(*pos + dir)) {
+                affected.push(*pos + dir);
+            }
         }
+    }
+    if affected.is_empty() {
+        return;
+    }
+    for (entity, pos) in tiles.iter() {
+        if affected.contains(&pos.0) {
+            commands.entity(entity).despawn();
+        }
+    }
+    let mut changed_chunks: Vec<Vector2Int> =
+        affected.iter().map(|p| chunks::chunk_of(*p)).collect();
+    changed_chunks.sort_by_key(|c| (c.x, c.y));
+    changed_chunks.dedup();
+    for (entity, chunk) in map_chunks.iter() {
+        if changed_chunks.contains(&chunk.0) {
+            commands.entity(entity).despawn();
+        }
+    }
+    chunks::spawn_chunks(
+        &mut commands,
+        &mut meshes,
+        &billboards,
+        &board,
+        changed_chunks,
+    );
+    for pos in affected {
+        spawn_tile(&mut commands, &assets, &billboards, &board, pos);
+    }
+}
 
-        commands
-            .spawn((
-                MeshMaterial3d(billboards.billboard_mat.clone()),
-                Sprite3dBillboard::new(billboards.billboard.clone()),
-                Sprite3d::from(floor_atlas_gen()),
-                Transform::from_xyz(x, 0.0, y)
-                    .with_rotation(Quat::from_rotation_x(-std::f32::consts::PI / 2.0)),
-            ))
-            .insert(Name::new(format!("Tile{}x{}", x, y)))
-            .insert(crate::board::MapTile)
-            .insert(PiecePos(*pos));
-        let mut rng = rand::thread_rng();
+/// Spawns the door and torches of the tile, the floor and walls are baked into the chunks.
+fn spawn_tile(
+    commands: &mut Commands,
+    assets: &ImageAssets,
+    billboards: &GameBillboards,
+    board: &CurrentBoard,
+    pos: Vector2Int,
+) {
+    let Some(surounding_elements) = BoardPieceToGen::from_pos(pos, board) else {
+        return;
+    };
+    let tile_type = &surounding_elements.tile_type;
+    let (x, y) = (pos.x as f32, pos.y as f32);
 
-        for el in surounding_elements
+    if tile_type == &TileType::Pit {
+        return;
+    }
+    if let Some(index) = renderer::door_sprite_index(tile_type) {
+        // doors face along the corridor, there are walls on the sides
+        let side_walls = surounding_elements
             .neighbours
             .iter()
-            .filter(|e| e.1.eq(&TileType::None))
-        {
-            if (pos.x + pos.y) % 4 == 0 && rng.gen_bool(0.5) {
-                let interval_counter = rng.gen_range(15..20);
-                let cur_index = rng.gen_range(0..15);
-                let min_intensity = rng.gen_range(25_000.0..40_000.0);
-                let flame_index = rng.gen_range(0..5);
-                let atlas = TextureAtlas {
-                    layout: assets.fire_layout.clone(),
-                    index: flame_index,
-                };
-                commands
-                    .spawn((
-                        MeshMaterial3d(billboards.unlit_mat.clone()),
-                        Sprite3dBillboard::new(billboards.fire_billboard.clone()),
-                        Sprite3d::from(atlas),
-                        Transform::from_xyz(
-                            x + (el.0.x_offset() * 0.8),
-                            1.499,
-                            y + (el.0.z_offset() * 0.8),
-                        )
-                        .with_rotation(el.0.rotation()),
-                        PiecePos(*pos),
-                        Animation::new_with_index(5, flame_index),
-                        FaceCamera,
-                        Name::new("TORCH"),
-                        LightPattern::from_chars(
-                            &"mmmmmaaaaammmmmaaaaaabcdefgabcdefg"
-                                .chars()
-                                .collect::<Vec<char>>(),
-                        ),
-                        Torch {
-                            cur_index,
-                            intensity_variation: 10_000.0,
-                            min_intensity,
-                            target_intensity: min_intensity,
-                            interval_counter,
-                        },
-                    ))
-                    .insert(crate::board::MapTile);
-            }
+            .filter(|e| matches!(e.0, BoardNeighbour::Left | BoardNeighbour::Right))
+            .all(|e| e.1.is_wall());
+        let facing = if side_walls {
+            BoardNeighbour::Down
+        } else {
+            BoardNeighbour::Left
+        };
+        commands.spawn((
+            MeshMaterial3d(billboards.billboard_transparent_mat.clone()),
+            Sprite3dBillboard::new(billboards.transparent_billboard.clone()),
+            Sprite3d::from(TextureAtlas {
+                layout: assets.layout.clone(),
+                index,
+            }),
+            Transform::from_xyz(x, 0.499, y).with_rotation(facing.rotation()),
+            Name::new("DOOR"),
+            renderer::DoorSprite,
+            crate::board::MapTile,
+            PiecePos(pos),
+        ));
+    }
+
+    let mut rng = rand::thread_rng();
+
+    for el in surounding_elements
+        .neighbours
+        .iter()
+        .filter(|e| e.1.eq(&TileType::None))
+    {
+        if (pos.x + pos.y) % 4 == 0 && rng.gen_bool(0.5) {
+            let interval_counter = rng.gen_range(15..20);
+            let cur_index = rng.gen_range(0..15);
+            let min_intensity = rng.gen_range(25_000.0..40_000.0);
+            let flame_index = rng.gen_range(0..5);
+            let atlas = TextureAtlas {
+                layout: assets.fire_layout.clone(),
+                index: flame_index,
+            };
+            commands
+                .spawn((
+                    MeshMaterial3d(billboards.unlit_mat.clone()),
+                    Sprite3dBillboard::new(billboards.fire_billboard.clone()),
+                    Sprite3d::from(atlas),
+                    Transform::from_xyz(
+                        x + (el.0.x_offset() * 0.8),
+                        1.499,
+                        y + (el.0.z_offset() * 0.8),
+                    )
+                    .with_rotation(el.0.rotation()),
+                    PiecePos(pos),
+                    Animation::new_with_index(5, flame_index),
+                    FaceCamera,
+                    Name::new("TORCH"),
+                    LightPattern::from_chars(
+                        &"mmmmmaaaaammmmmaaaaaabcdefgabcdefg"
+                            .chars()
+                            .collect::<Vec<char>>(),
+                    ),
+                    Torch {
+                        cur_index,
+                        intensity_variation: 10_000.0,
+                        min_intensity,
+                        target_intensity: min_intensity,
+                        interval_counter,
+                    },
+                ))
+                .insert(crate::board::MapTile);
         }
     }
-    let duration = start.elapsed();
-    error!("World generation: {}", duration.as_micros());
 }
 
 fn update_animation(mut query: Query<&mut Animation>) {
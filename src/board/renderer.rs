@@ -1,28 +1,76 @@
-use crate::{consts, gfx::GameBillboards, vectors::Vector2Int, FaceCamera, ImageAssets};
+use crate::{consts, gfx::GameBillboards, rules, vectors::Vector2Int, FaceCamera, ImageAssets};
 use bevy::prelude::*;
 use bevy_sprite3d::{Sprite3d, Sprite3dBillboard};
 use rand::prelude::SliceRandom;
 
-use super::{GameObject, Piece, PiecePos, PlayerControl};
+use super::{
+    components::{CurrentBoard, Item, TileType, Trap, TrapKind},
+    state::BoardQuery,
+    GameObject, MapTile, Piece, PiecePos, PlayerControl,
+};
 const RENDER_DISTANCE: i32 = 10;
 
+/// Door standing on the tile, rebuilt with the tile when it opens.
+#[derive(Component)]
+pub struct DoorSprite;
+
+/// Item lying on the board, despawned when it is no longer on the board.
+#[derive(Component)]
+pub struct ItemSprite;
+
+/// Sprite of the discovered trap, despawned when the trap is disarmed or hidden again by undo.
+#[derive(Component)]
+pub struct TrapSprite(Entity);
+
+pub fn visibility(board: &BoardQuery, player: Vector2Int, pos: Vector2Int) -> Visibility {
+    if pos.manhattan(player) <= RENDER_DISTANCE && rules::line_of_sight(board, player, pos) {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+pub fn door_sprite_index(tile: &TileType) -> Option<usize> {
+    match tile {
+        TileType::Door { open: false } => Some(consts::CLOSED_DOOR),
+        TileType::Door { open: true } => Some(consts::OPEN_DOOR),
+        TileType::LockedDoor => Some(consts::LOCKED_DOOR),
+        _ => None,
+    }
+}
+
+pub fn wall_sprite_index(tile: &TileType) -> usize {
+    match tile {
+        TileType::CrackedWall => consts::CRACKED_WALL,
+        _ => consts::WALL,
+    }
+}
+
+/// Hazards are drawn instead of the floor.
+pub fn hazard_sprite_index(tile: &TileType) -> Option<usize> {
+    match tile {
+        TileType::Spikes => Some(consts::SPIKES),
+        TileType::Lava => Some(consts::LAVA),
+        TileType::Water => Some(consts::WATER),
+        TileType::Ice => Some(consts::ICE),
+        _ => None,
+    }
+}
+
 pub fn spawn_piece_renderer(
     mut commands: Commands,
     assets: Res<ImageAssets>,
     billboards: Res<GameBillboards>,
     player_query: Query<&PiecePos, With<PlayerControl>>,
     query: Query<(Entity, &PiecePos, &Piece), Added<Piece>>,
+    board: BoardQuery,
 ) {
     let player_pos = player_query
         .single()
         .map_or(Vector2Int::default(), |e| e.0.to_owned());
 
     for (entity, pos, piece) in query.iter() {
-        let vis = if pos.manhattan(player_pos) <= RENDER_DISTANCE {
-            Visibility::Inherited
-        } else {
-            Visibility::Hidden
-        };
+        let vis = visibility(&board, player_pos, pos.0);
         let atlas = TextureAtlas {
             layout: assets.layout.clone(),
             index: if piece == &Piece::Player { 26 } else { 125 },
@@ -47,7 +95,11 @@ pub fn spawn_piece_renderer(
 pub fn update_piece(
     mut query: Query<
         (&PiecePos, &mut Transform),
-        (Changed<PiecePos>, Without<crate::board::MapTile>),
+        (
+            Changed<PiecePos>,
+            Without<crate::board::MapTile>,
+            Without<Piece>,
+        ),
     >,
 ) {
     for (pos, mut transform) in query.iter_mut() {
@@ -56,31 +108,132 @@ pub fn update_piece(
 }
 
 pub fn update_tile_visibility(
-    player_query: Query<(&PiecePos, &PlayerControl), Changed<PiecePos>>,
+    player_query: Query<Ref<PiecePos>, With<PlayerControl>>,
     mut q: Query<(&mut Visibility, &PiecePos)>,
+    rebuilt: Query<(), Added<MapTile>>,
+    board: BoardQuery,
 ) {
-    let Ok((player_pos, _)) = player_query.single() else {
+    let Ok(player_pos) = player_query.single() else {
         return;
     };
-    let player = **player_pos;
-    q.par_iter_mut().for_each(|(mut visibility, pos)| {
-        let new_vis = if pos.manhattan(player) <= RENDER_DISTANCE {
-            Visibility::Inherited
+    // opened doors change what can be seen, rebuilt tiles start visible
+    if !player_pos.is_changed() && !board.is_changed() && rebuilt.is_empty() {
+        return;
+    }
+    let player = player_pos.0;
+    q.par_iter_mut().for_each(|(mut vis, pos)| {
+        vis.set_if_neq(visibility(&board, player, pos.0));
+    });
+}
+
+/// Spawns the sprites of the items on the board and removes the picked up ones.
+pub fn sync_items(
+    mut commands: Commands,
+    assets: Res<ImageAssets>,
+    billboards: Res<GameBillboards>,
+    player_query: Query<&PiecePos, With<PlayerControl>>,
+    q: Query<(Entity, &PiecePos), With<ItemSprite>>,
+    board: BoardQuery,
+    current: Res<CurrentBoard>,
+) {
+    if !current.is_changed() {
+        return;
+    }
+    let player_pos = player_query.single().map_or(Vector2Int::default(), |p| p.0);
+    let mut shown = Vec::new();
+    for (entity, pos) in q.iter() {
+        if current.items.contains_key(&pos.0) {
+            shown.push(pos.0);
         } else {
-            Visibility::Hidden
+            commands.entity(entity).despawn();
+        }
+    }
+    for (pos, item) in current.items.iter() {
+        if shown.contains(pos) {
+            continue;
+        }
+        let index = match item {
+            Item::Key => consts::KEY,
+            Item::Bomb => consts::BOMB,
         };
-        visibility.set_if_neq(new_vis);
-    });
+        let atlas = TextureAtlas {
+            layout: assets.layout.clone(),
+            index,
+        };
+        commands.spawn((
+            Transform::from_xyz(pos.x as f32, 0.5, pos.y as f32),
+            Sprite3d::from(atlas),
+            Sprite3dBillboard::new(billboards.transparent_billboard.clone()),
+            MeshMaterial3d(billboards.billboard_transparent_mat.clone()),
+            Name::new(format!("{:?}", item)),
+            FaceCamera,
+            GameObject,
+            ItemSprite,
+            PiecePos(*pos),
+            visibility(&board, player_pos, *pos),
+        ));
+    }
+}
+
+pub fn sync_traps(
+    mut commands: Commands,
+    assets: Res<ImageAssets>,
+    billboards: Res<GameBillboards>,
+    player_query: Query<&PiecePos, With<PlayerControl>>,
+    traps: Query<(Entity, &Trap, &PiecePos), Changed<Trap>>,
+    sprites: Query<(Entity, &TrapSprite)>,
+    board: BoardQuery,
+) {
+    let player_pos = player_query.single().map_or(Vector2Int::default(), |p| p.0);
+    for (entity, trap, pos) in traps.iter() {
+        let sprite = sprites.iter().find(|(_, s)| s.0 == entity).map(|(e, _)| e);
+        match (sprite, trap.is_known()) {
+            (Some(sprite), false) => commands.entity(sprite).despawn(),
+            (None, true) => {
+                let index = match trap.kind {
+                    TrapKind::PressurePlate => consts::PRESSURE_PLATE,
+                    TrapKind::Dart => consts::DART_TRAP,
+                    TrapKind::Teleport => consts::TELEPORT_TRAP,
+                    TrapKind::Alarm => consts::ALARM_TRAP,
+                };
+                let atlas = TextureAtlas {
+                    layout: assets.layout.clone(),
+                    index,
+                };
+                commands.spawn((
+                    // lies on the floor
+                    Transform::from_xyz(pos.x as f32, 0.01, pos.y as f32)
+                        .with_rotation(Quat::from_rotation_x(-std::f32::consts::PI / 2.0)),
+                    Sprite3d::from(atlas),
+                    Sprite3dBillboard::new(billboards.transparent_billboard.clone()),
+                    MeshMaterial3d(billboards.billboard_transparent_mat.clone()),
+                    Name::new(format!("{:?}", trap.kind)),
+                    crate::board::MapTile,
+                    TrapSprite(entity),
+                    *pos,
+                    visibility(&board, player_pos, pos.0),
+                ));
+            }
+            _ => {}
+        }
+    }
 }
 
 pub fn dig_the_grave(
+    mut commands: Commands,
     mut removed: RemovedComponents<Piece>,
-    mut query: Query<(&mut Sprite3d, &mut Transform)>,
+    mut query: Query<(&mut Sprite3d, &mut Transform, &PiecePos)>,
+    board: Res<CurrentBoard>,
+    speed: Res<super::animations::AnimationSpeed>,
 ) {
     for e in removed.read() {
-        let Ok((mut sprite, mut transform)) = query.get_mut(e) else {
+        let Ok((mut sprite, mut transform, pos)) = query.get_mut(e) else {
             return;
         };
+        if board.tiles.get(&pos.0) == Some(&TileType::Pit) {
+            super::animations::animate_fall(&mut commands, &speed, e, &transform);
+            continue;
+        }
         let atlas = sprite.texture_atlas.as_mut().unwrap();
         atlas.index = *consts::GRAVES.choose(&mut rand::thread_rng()).unwrap();
         transform.translation += Vec3::NEG_Y * 0.2;
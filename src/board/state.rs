@@ -0,0 +1,69 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use super::components::*;
+use crate::{rules::BoardState, vectors::Vector2Int};
+
+/// Board for the rules inside of the systems.
+#[derive(SystemParam)]
+pub struct BoardQuery<'w, 's> {
+    board: Res<'w, CurrentBoard>,
+    occupancy: Res<'w, Occupancy>,
+    trap_index: Res<'w, TrapIndex>,
+    pieces: Query<'w, 's, &'static Piece, With<Health>>,
+    traps: Query<'w, 's, &'static Trap>,
+}
+
+impl BoardQuery<'_, '_> {
+    pub fn is_changed(&self) -> bool {
+        self.board.is_changed()
+    }
+}
+
+impl BoardState for BoardQuery<'_, '_> {
+    type Id = Entity;
+
+    fn tile(&self, pos: Vector2Int) -> Option<&TileType> {
+        self.board.tiles.get(&pos)
+    }
+
+    fn occupant(&self, pos: Vector2Int) -> Option<Entity> {
+        self.occupancy.get(pos)
+    }
+
+    fn living_piece(&self, id: Entity) -> Option<&Piece> {
+        self.pieces.get(id).ok()
+    }
+
+    fn trap(&self, pos: Vector2Int) -> Option<&Trap> {
+        let entity = self.trap_index.get(pos)?;
+        self.traps.get(entity).ok().filter(|trap| trap.armed)
+    }
+}
+
+/// Board for the rules inside of the actions executed on the world.
+pub struct WorldBoard<'w>(pub &'w World);
+
+impl BoardState for WorldBoard<'_> {
+    type Id = Entity;
+
+    fn tile(&self, pos: Vector2Int) -> Option<&TileType> {
+        self.0.get_resource::<CurrentBoard>()?.tiles.get(&pos)
+    }
+
+    fn occupant(&self, pos: Vector2Int) -> Option<Entity> {
+        self.0.get_resource::<Occupancy>()?.get(pos)
+    }
+
+    fn living_piece(&self, id: Entity) -> Option<&Piece> {
+        let entity = self.0.get_entity(id).ok()?;
+        if !entity.contains::<Health>() {
+            return None;
+        }
+        entity.get::<Piece>()
+    }
+
+    fn trap(&self, pos: Vector2Int) -> Option<&Trap> {
+        let entity = self.0.get_resource::<TrapIndex>()?.get(pos)?;
+        self.0.get::<Trap>(entity).filter(|trap| trap.armed)
+    }
+}
@@ -0,0 +1,354 @@
+//! Small hand-made rooms stamped into the free space of the generated dungeons.
+//!
+//! Vaults use the glyphs of the level files, `+` marks the floor connected to the rest of the dungeon.
+use std::collections::VecDeque;
+
+use bevy::platform::collections::HashMap;
+use rand::{seq::SliceRandom, Rng, RngCore};
+
+use super::{
+    components::*,
+    level::{self, Cell},
+};
+use crate::vectors::Vector2Int;
+
+const DIRECTIONS: [Vector2Int; 4] = [
+    Vector2Int::UP,
+    Vector2Int::DOWN,
+    Vector2Int::LEFT,
+    Vector2Int::RIGHT,
+];
+
+const ENTRANCE: char = '+';
+/// Empty tiles kept around the vault, so it does not merge with the rooms.
+const MARGIN: i32 = 1;
+const PLACEMENT_ATTEMPTS: usize = 50;
+
+pub const VAULTS: [(&str, &str); 3] = [
+    (
+        "treasure closet",
+        "\
+fkf
+fEf
+f+f",
+    ),
+    (
+        "pit arena",
+        "\
+fffffff
+fpfffpf
+ffpEpff
++ffpff+
+ffpEpff
+fpfffpf
+fffffff",
+    ),
+    (
+        "monster den",
+        "\
+*fffff*
+ffEfEff
+fEfffEf
+fffffff
+**f+f**",
+    ),
+];
+
+#[derive(Clone)]
+pub struct Vault {
+    tiles: Vec<(Vector2Int, Cell)>,
+    entrances: Vec<Vector2Int>,
+}
+
+impl Vault {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut tiles = Vec::new();
+        let mut entrances = Vec::new();
+        for (y, line) in text.lines().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                let pos = Vector2Int::new(x as i32, y as i32);
+                if c == ENTRANCE {
+                    entrances.push(pos);
+                    tiles.push((pos, (TileType::BaseFloor, None, None)));
+                    continue;
+                }
+                if let Some(cell) =
+                    level::parse_glyph(c).map_err(|e| format!("{} at {}x{}", e, x, y))?
+                {
+                    tiles.push((pos, cell));
+                }
+            }
+        }
+        if entrances.is_empty() {
+            return Err("Vault needs an entrance".into());
+        }
+        Ok(Self { tiles, entrances })
+    }
+
+    /// Rotated by `quarter_turns` times 90 degrees and optionally mirrored, moved back to `0x0`.
+    fn transformed(&self, quarter_turns: u8, mirror: bool) -> Self {
+        let transform = |mut v: Vector2Int| {
+            for _ in 0..quarter_turns % 4 {
+                v = Vector2Int::new(-v.y, v.x);
+            }
+            if mirror {
+                v.x = -v.x;
+            }
+            v
+        };
+        let mut vault = Self {
+            tiles: self
+                .tiles
+                .iter()
+                .map(|(pos, cell)| (transform(*pos), cell.clone()))
+                .collect(),
+            entrances: self.entrances.iter().map(|pos| transform(*pos)).collect(),
+        };
+        let min = vault.bounds().0;
+        vault.shift(Vector2Int::new(-min.x, -min.y));
+        vault
+    }
+
+    fn shift(&mut self, offset: Vector2Int) {
+        for (pos, _) in self.tiles.iter_mut() {
+            *pos += offset;
+        }
+        for pos in self.entrances.iter_mut() {
+            *pos += offset;
+        }
+    }
+
+    fn contains(&self, pos: Vector2Int) -> bool {
+        self.tiles.iter().any(|(p, _)| *p == pos)
+    }
+
+    fn bounds(&self) -> (Vector2Int, Vector2Int) {
+        let min_x = self.tiles.iter().map(|t| t.0.x).min().unwrap_or_default();
+        let max_x = self.tiles.iter().map(|t| t.0.x).max().unwrap_or_default();
+        let min_y = self.tiles.iter().map(|t| t.0.y).min().unwrap_or_default();
+        let max_y = self.tiles.iter().map(|t| t.0.y).max().unwrap_or_default();
+        (Vector2Int::new(min_x, min_y), Vector2Int::new(max_x, max_y))
+    }
+
+    /// Vault with its margin does not touch any of the tiles.
+    fn fits(&self, tiles: &HashMap<Vector2Int, TileType>) -> bool {
+        let (min, max) = self.bounds();
+        (min.x - MARGIN..=max.x + MARGIN)
+            .flat_map(|x| (min.y - MARGIN..=max.y + MARGIN).map(move |y| Vector2Int::new(x, y)))
+            .all(|pos| !tiles.contains_key(&pos))
+    }
+}
+
+/// Places random vaults around the dungeon and connects their entrances to the closest floor.
+pub fn stamp_vaults(board: &mut CurrentBoard, amount: usize, rng: &mut dyn RngCore) {
+    let max = Vector2Int::new(
+        board.tiles.keys().map(|p| p.x).max().unwrap_or_default(),
+        board.tiles.keys().map(|p| p.y).max().unwrap_or_default(),
+    );
+    for _ in 0..amount {
+        let Some((name, text)) = VAULTS.choose(rng) else {
+            return;
+        };
+        let vault = match Vault::parse(text) {
+            Ok(vault) => vault.transformed(rng.gen_range(0..4), rng.gen_bool(0.5)),
+            Err(e) => {
+                bevy::log::error!("Invalid vault {}: {}", name, e);
+                continue;
+            }
+        };
+        let placed = (0..PLACEMENT_ATTEMPTS).find_map(|_| {
+            // keep the coordinates positive, the board is printed from 0x0
+            let offset = Vector2Int::new(
+                rng.gen_range(MARGIN..=max.x + MARGIN * 2 + 1),
+                rng.gen_range(MARGIN..=max.y + MARGIN * 2 + 1),
+            );
+            let mut vault = vault.clone();
+            vault.shift(offset);
+            vault.fits(&board.tiles).then_some(vault)
+        });
+        let Some(vault) = placed else {
+            continue;
+        };
+        bevy::log::info!("Vault {} placed at {:?}", name, vault.bounds().0);
+        stamp(&vault, board);
+    }
+}
+
+fn stamp(vault: &Vault, board: &mut CurrentBoard) {
+    for (pos, (tile, spawn, item)) in vault.tiles.iter() {
+        board.tiles.insert(*pos, tile.clone());
+        if let Some(piece) = spawn {
+            board.spawn_points.insert(*pos, piece.clone());
+        }
+        if let Some(item) = item {
+            board.items.insert(*pos, item.clone());
+        }
+    }
+    for entrance in vault.entrances.iter() {
+        // corridor starts outside of the vault and goes around it
+        let Some(start) = DIRECTIONS
+            .iter()
+            .map(|dir| *entrance + *dir)
+            .find(|pos| !vault.contains(*pos))
+        else {
+            continue;
+        };
+        let Some(corridor) = corridor(board, vault, start) else {
+            continue;
+        };
+        for pos in corridor {
+            board.tiles.insert(pos, TileType::BaseFloor);
+        }
+    }
+}
+
+/// Shortest corridor from `start` through the empty tiles to the closest floor outside of the vault.
+///
+/// Only the empty tiles are returned, the corridor stops at the first floor it reaches,
+/// so the hazards, doors and the vaults stamped before stay as they are.
+fn corridor(board: &CurrentBoard, vault: &Vault, start: Vector2Int) -> Option<Vec<Vector2Int>> {
+    // one tile of space around everything, like the margin of the vaults
+    let max = Vector2Int::new(
+        board.tiles.keys().map(|p| p.x).max()? + 1,
+        board.tiles.keys().map(|p| p.y).max()? + 1,
+    );
+    let mut came_from = HashMap::from([(start, start)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(pos) = queue.pop_front() {
+        match board.tiles.get(&pos) {
+            Some(TileType::BaseFloor) if !vault.contains(pos) => {
+                let mut corridor = Vec::new();
+                let mut current = pos;
+                while current != start {
+                    current = came_from[&current];
+                    corridor.push(current);
+                }
+                return Some(corridor);
+            }
+            None | Some(TileType::None) if !vault.contains(pos) => {
+                for next in DIRECTIONS.iter().map(|dir| pos + *dir) {
+                    let inside = (0..=max.x).contains(&next.x) && (0..=max.y).contains(&next.y);
+                    if inside && !came_from.contains_key(&next) {
+                        came_from.insert(next, pos);
+                        queue.push_back(next);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn at(x: i32, y: i32) -> Vector2Int {
+        Vector2Int::new(x, y)
+    }
+
+    fn closet() -> Vault {
+        Vault::parse(VAULTS[0].1).unwrap()
+    }
+
+    /// Tiles the player can walk to, the doors are opened on the way.
+    fn reachable(board: &CurrentBoard, from: Vector2Int) -> HashSet<Vector2Int> {
+        let mut seen = HashSet::from([from]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(pos) = queue.pop_front() {
+            for next in DIRECTIONS.iter().map(|dir| pos + *dir) {
+                let walkable = board.tiles.get(&next).is_some_and(|t| {
+                    t.move_cost(false).is_some()
+                        || matches!(t, TileType::Door { .. } | TileType::LockedDoor)
+                });
+                if walkable && seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        seen
+    }
+
+    fn positions(vault: &Vault) -> HashSet<Vector2Int> {
+        vault.tiles.iter().map(|(pos, _)| *pos).collect()
+    }
+
+    #[test]
+    fn every_vault_parses() {
+        for (name, text) in VAULTS {
+            if let Err(e) = Vault::parse(text) {
+                panic!("{}: {}", name, e);
+            }
+        }
+    }
+
+    #[test]
+    fn quarter_turn_keeps_the_vault_at_the_origin() {
+        let vault = Vault::parse("fff\n+ff").unwrap();
+        let turned = vault.transformed(1, false);
+        assert_eq!(turned.bounds(), (at(0, 0), at(1, 2)));
+        assert_eq!(turned.entrances, vec![at(0, 0)]);
+        let around = vault.transformed(4, false);
+        assert_eq!(positions(&around), positions(&vault));
+        assert_eq!(around.entrances, vault.entrances);
+    }
+
+    #[test]
+    fn mirror_flips_the_columns() {
+        let vault = Vault::parse("fff\n+ff").unwrap();
+        let mirrored = vault.transformed(0, true);
+        assert_eq!(mirrored.bounds(), vault.bounds());
+        assert_eq!(mirrored.entrances, vec![at(2, 1)]);
+    }
+
+    #[test]
+    fn vault_keeps_the_margin() {
+        let tiles = HashMap::from([(at(0, 0), TileType::BaseFloor)]);
+        let mut vault = closet();
+        vault.shift(at(MARGIN + 1, 0));
+        assert!(vault.fits(&tiles));
+        vault.shift(at(-1, 0));
+        assert!(!vault.fits(&tiles), "touches the floor");
+    }
+
+    #[test]
+    fn stamped_vaults_are_reachable() {
+        let mut stamped = 0;
+        for seed in 0..20 {
+            let mut board = CurrentBoard::parse("Pffff\nfffff\nfffff").unwrap();
+            stamp_vaults(&mut board, 2, &mut StdRng::seed_from_u64(seed));
+            let reachable = reachable(&board, at(0, 0));
+            for pos in board.spawn_points.keys().chain(board.items.keys()) {
+                assert!(
+                    reachable.contains(pos),
+                    "seed {}: {:?} is cut off",
+                    seed,
+                    pos
+                );
+            }
+            // every vault has a monster
+            stamped += board.spawn_points.len() - 1;
+        }
+        assert!(stamped >= 20, "only {} vaults stamped", stamped);
+    }
+
+    #[test]
+    fn corridor_goes_around_the_existing_tiles() {
+        let mut board = CurrentBoard::parse("\n\n\nPfffd").unwrap();
+        let before = board.clone();
+        let mut vault = closet();
+        vault.shift(at(6, 0));
+        stamp(&vault, &mut board);
+        for (pos, tile) in before.tiles.iter() {
+            assert_eq!(board.tiles.get(pos), Some(tile), "{:?} overwritten", pos);
+        }
+        assert!(reachable(&board, at(0, 3)).contains(&at(7, 2)));
+        let carved = board.tiles.len() - before.tiles.len() - vault.tiles.len();
+        assert_eq!(carved, 6, "shortest way around the door");
+    }
+}
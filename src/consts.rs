@@ -17,3 +17,20 @@ pub const BG_COLOR: Color = Color::srgb(0.08, 0.08, 0.115);
 // pub const ENEMY_SKULL : usize = 622;
 // pub const POTION : usize = 671;
 pub const GRAVES: [usize; 5] = [686, 687, 686, 687, 688];
+pub const CLOSED_DOOR: usize = 444;
+pub const OPEN_DOOR: usize = 447;
+pub const LOCKED_DOOR: usize = 441;
+pub const KEY: usize = 571;
+pub const BOMB: usize = 486;
+pub const HEART: usize = 529;
+pub const EMPTY_HEART: usize = 530;
+pub const WALL: usize = 843;
+pub const CRACKED_WALL: usize = 892;
+pub const SPIKES: usize = 22;
+pub const LAVA: usize = 894;
+pub const WATER: usize = 253;
+pub const ICE: usize = 197;
+pub const PRESSURE_PLATE: usize = 1068;
+pub const DART_TRAP: usize = 1065;
+pub const TELEPORT_TRAP: usize = 1069;
+pub const ALARM_TRAP: usize = 1064;
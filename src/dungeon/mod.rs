@@ -24,14 +24,12 @@ impl Room {
             Vector2Int::new(self.a.x, self.b.y),
         ]
     }
-    pub fn random_point(&self) -> Vector2Int {
-        let mut rng = thread_rng();
+    pub fn random_point(&self, rng: &mut dyn RngCore) -> Vector2Int {
         let x = rng.gen_range(self.a.x..=self.b.x);
         let y = rng.gen_range(self.a.y..=self.b.y);
         Vector2Int::new(x, y)
     }
-    pub fn random_point_without_walls(&self) -> Vector2Int {
-        let mut rng = thread_rng();
+    pub fn random_point_without_walls(&self, rng: &mut dyn RngCore) -> Vector2Int {
         let x = rng.gen_range(self.a.x + 1..=self.b.x - 1);
         let y = rng.gen_range(self.a.y + 1..=self.b.y - 1);
         Vector2Int::new(x, y)
@@ -82,15 +80,16 @@ impl Area {
             }
         }
     }
-    pub fn join_rooms(&self, a: &Room, b: &Room) -> Vec<Vector2Int> {
-        self.tunneler.connect(a.random_point(), b.random_point())
+    pub fn join_rooms(&self, a: &Room, b: &Room, rng: &mut dyn RngCore) -> Vec<Vector2Int> {
+        let (a, b) = (a.random_point(rng), b.random_point(rng));
+        self.tunneler.connect(a, b, rng)
     }
-    pub fn generate_rooms(&mut self) {
+    pub fn generate_rooms(&mut self, rng: &mut dyn RngCore) {
         self.rooms = vec![
             Room::new(Vector2Int::new(0, 0), Vector2Int::new(4, 6)),
             Room::new(Vector2Int::new(10, 2), Vector2Int::new(14, 8)),
         ];
-        self.paths = vec![self.join_rooms(&self.rooms[0], &self.rooms[1])];
+        self.paths = vec![self.join_rooms(&self.rooms[0], &self.rooms[1], rng)];
     }
     pub fn to_tiles(&self) -> HashSet<Vector2Int> {
         self.rooms
@@ -125,9 +124,9 @@ impl Area {
         pairs.sort_by(|a, b| a.0.cmp(&b.0));
         (pairs[0].1, pairs[0].2)
     }
-    pub fn join_area(&self, other: &Area) -> Vec<Vector2Int> {
+    pub fn join_area(&self, other: &Area, rng: &mut dyn RngCore) -> Vec<Vector2Int> {
         let rooms = self.find_closest_room_pair(other);
-        self.join_rooms(rooms.0, rooms.1)
+        self.join_rooms(rooms.0, rooms.1, rng)
     }
 }
 const AREA_SPACING: i32 = 4;
@@ -153,14 +152,14 @@ impl Dungeon {
         // insert the index to appropriate row vec
         self.grid[idx % row_count].push(idx);
     }
-    pub fn generate(&mut self) {
+    pub fn generate(&mut self, rng: &mut dyn RngCore) {
         for area in self.areas.iter_mut() {
-            area.generate_rooms();
+            area.generate_rooms(rng);
         }
         self.position_areas();
-        self.connect_areas();
+        self.connect_areas(rng);
     }
-    fn connect_areas(&mut self) {
+    fn connect_areas(&mut self, rng: &mut dyn RngCore) {
         // connect areas based on their grid location
         let mut pairs = Vec::new();
         for (y, row) in self.grid.iter().enumerate() {
@@ -176,7 +175,7 @@ impl Dungeon {
             }
         }
         for pair in pairs {
-            let path = self.areas[*pair.0].join_area(&self.areas[pair.1]);
+            let path = self.areas[*pair.0].join_area(&self.areas[pair.1], rng);
             self.areas[*pair.0].paths.push(path);
         }
     }
@@ -225,14 +224,14 @@ impl Dungeon {
 }
 
 pub trait Tunneler {
-    fn connect(&self, a: Vector2Int, b: Vector2Int) -> Vec<Vector2Int>;
+    fn connect(&self, a: Vector2Int, b: Vector2Int, rng: &mut dyn RngCore) -> Vec<Vector2Int>;
 }
 
 pub struct LShapeTunneler;
 impl Tunneler for LShapeTunneler {
     // connects two points by forming an L shaped connection
     // initial direction (hor / ver) is the one whith the biggest coordinate difference
-    fn connect(&self, a: Vector2Int, b: Vector2Int) -> Vec<Vector2Int> {
+    fn connect(&self, a: Vector2Int, b: Vector2Int, _rng: &mut dyn RngCore) -> Vec<Vector2Int> {
         let d = b - a;
         let (hor_y, ver_x) = match d.x > d.y {
             true => (a.y, b.x),
@@ -254,10 +253,9 @@ impl Tunneler for RandomTunneler {
     // connects two points by taking a random direction (hor / ver) towards the target
     // choice chance is determined by a current coordinate difference
     // (it is most likely to pick a dir with the biggest diff)
-    fn connect(&self, a: Vector2Int, b: Vector2Int) -> Vec<Vector2Int> {
+    fn connect(&self, a: Vector2Int, b: Vector2Int, rng: &mut dyn RngCore) -> Vec<Vector2Int> {
         let mut cur = a;
         let mut path = Vec::new();
-        let mut rng = thread_rng();
 
         while cur != b {
             path.push(cur);
@@ -266,7 +264,7 @@ impl Tunneler for RandomTunneler {
             // build weights
             let dist = WeightedIndex::new(dirs.iter().map(|d| d.abs())).unwrap();
             // pick a dir idx (0 or 1)
-            let dir_idx = dist.sample(&mut rng);
+            let dir_idx = dist.sample(rng);
             // create a normalized step vector in a single direction
             let dv = match dir_idx {
                 0 => Vector2Int::new(dirs[0] / dirs[0].abs(), 0),
@@ -3,6 +3,9 @@ use bevy_sprite3d::Billboard;
 
 use crate::{states::MainGameState, ImageAssets};
 
+/// Color the board sprites are tinted with.
+pub const BILLBOARD_TINT: LinearRgba = LinearRgba::new(0.486, 0.385, 0.223, 1.000);
+
 #[derive(Resource, Reflect)]
 #[reflect(Resource)]
 pub struct GameBillboards {
@@ -12,6 +15,8 @@ pub struct GameBillboards {
     pub billboard_transparent_mat: Handle<StandardMaterial>,
     pub fire_billboard: Handle<Billboard>,
     pub unlit_mat: Handle<StandardMaterial>,
+    /// Material of the map chunks, the tint comes from the vertex colors of their meshes
+    pub chunk_mat: Handle<StandardMaterial>,
 }
 
 pub struct GfxPlugin;
@@ -52,7 +57,13 @@ fn build_billboards(
     ));
     let billboard_mat = materials.add(StandardMaterial {
         base_color_texture: assets.image.clone().into(),
-        base_color: Color::LinearRgba(LinearRgba::new(0.486, 0.385, 0.223, 1.000)),
+        base_color: Color::LinearRgba(BILLBOARD_TINT),
+        ..bevy_sprite3d::utils::material()
+    });
+    let chunk_mat = materials.add(StandardMaterial {
+        base_color_texture: assets.image.clone().into(),
+        // the tiles hidden by the fog have transparent vertices
+        alpha_mode: AlphaMode::Mask(0.5),
         ..bevy_sprite3d::utils::material()
     });
     let billboard_transparent_mat = materials.add(StandardMaterial {
@@ -75,5 +86,6 @@ fn build_billboards(
         billboard_mat,
         billboard_transparent_mat,
         unlit_mat,
+        chunk_mat,
     });
 }
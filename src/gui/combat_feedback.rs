@@ -0,0 +1,220 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    image::ImageSampler,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use rand::Rng;
+
+use crate::{
+    board::components::*,
+    consts::{self, MY_ACCENT_COLOR},
+};
+
+const NUMBER_TIME: f32 = 0.8;
+const NUMBER_RISE: f32 = 0.6;
+const SHAKE_TIME: f32 = 0.25;
+const SHAKE_STRENGTH: f32 = 0.15;
+/// The vignette shows up when the player has that much health or less.
+const LOW_HEALTH: u32 = 1;
+const VIGNETTE_SIZE: u32 = 64;
+
+/// Damage text following the point where the piece was hit.
+#[derive(Component)]
+pub struct DamageNumber {
+    origin: Vec3,
+    timer: Timer,
+}
+
+#[derive(Component)]
+pub struct LowHealthVignette;
+
+/// Offset of the camera added by the shake, removed again in the next frame.
+#[derive(Resource)]
+pub struct ScreenShake {
+    timer: Timer,
+    applied: Vec3,
+    written: Vec3,
+}
+
+impl Default for ScreenShake {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(SHAKE_TIME, TimerMode::Once);
+        timer.tick(timer.duration());
+        Self {
+            timer,
+            applied: Vec3::ZERO,
+            written: Vec3::ZERO,
+        }
+    }
+}
+
+pub fn spawn_damage_numbers(
+    mut commands: Commands,
+    mut events: EventReader<PieceDamaged>,
+    asset_server: Res<AssetServer>,
+    player: Query<(), With<PlayerControl>>,
+    mut shake: ResMut<ScreenShake>,
+) {
+    for event in events.read() {
+        let is_player = player.contains(event.piece);
+        if is_player {
+            shake.timer.reset();
+        }
+        commands.spawn((
+            Text::new(format!("-{}", event.amount)),
+            TextFont {
+                font: asset_server.load(consts::BASE_FONT),
+                // killing blows stand out
+                font_size: if event.health == 0 { 32.0 } else { 24.0 },
+                ..default()
+            },
+            TextColor(if is_player {
+                Color::srgb(0.9, 0.2, 0.2)
+            } else {
+                MY_ACCENT_COLOR
+            }),
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            Visibility::Hidden,
+            bevy::picking::Pickable::IGNORE,
+            DamageNumber {
+                origin: Vec3::new(event.pos.x as f32, 0.8, event.pos.y as f32),
+                timer: Timer::from_seconds(NUMBER_TIME, TimerMode::Once),
+            },
+            GameObject,
+        ));
+    }
+}
+
+pub fn move_damage_numbers(
+    mut commands: Commands,
+    time: Res<Time>,
+    camera: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut numbers: Query<(
+        Entity,
+        &mut DamageNumber,
+        &mut Node,
+        &mut TextColor,
+        &mut Visibility,
+    )>,
+) {
+    let Ok((camera, camera_transform)) = camera.single() else {
+        return;
+    };
+    for (entity, mut number, mut node, mut color, mut visibility) in numbers.iter_mut() {
+        if number.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let progress = number.timer.fraction();
+        let world_pos = number.origin + Vec3::Y * NUMBER_RISE * progress;
+        let Ok(screen_pos) = camera.world_to_viewport(camera_transform, world_pos) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        node.left = Val::Px(screen_pos.x);
+        node.top = Val::Px(screen_pos.y);
+        color.0 = color.0.with_alpha(1.0 - progress);
+    }
+}
+
+pub fn shake_camera(
+    time: Res<Time>,
+    mut shake: ResMut<ScreenShake>,
+    mut camera: Query<&mut Transform, With<Camera3d>>,
+) {
+    let Ok(mut transform) = camera.single_mut() else {
+        return;
+    };
+    if shake.timer.finished() && shake.applied == Vec3::ZERO {
+        return;
+    }
+    shake.timer.tick(time.delta());
+    // the camera controller could move the camera in the meantime, then there is nothing to undo
+    let base = if transform.translation == shake.written {
+        transform.translation - shake.applied
+    } else {
+        transform.translation
+    };
+    let strength = SHAKE_STRENGTH * (1.0 - shake.timer.fraction());
+    let mut rng = rand::thread_rng();
+    shake.applied = if shake.timer.finished() {
+        Vec3::ZERO
+    } else {
+        Vec3::new(
+            rng.gen_range(-strength..=strength),
+            rng.gen_range(-strength..=strength),
+            rng.gen_range(-strength..=strength),
+        )
+    };
+    transform.translation = base + shake.applied;
+    shake.written = transform.translation;
+}
+
+/// Transparent in the middle and opaque at the edges, tinted when displayed.
+fn vignette_image() -> Image {
+    let mut data = Vec::with_capacity((VIGNETTE_SIZE * VIGNETTE_SIZE * 4) as usize);
+    for y in 0..VIGNETTE_SIZE {
+        for x in 0..VIGNETTE_SIZE {
+            let uv = (Vec2::new(x as f32, y as f32) + 0.5) / VIGNETTE_SIZE as f32;
+            let distance = (uv * 2.0 - Vec2::ONE).length();
+            let alpha = ((distance - 0.6) / 0.8).clamp(0.0, 1.0);
+            data.extend([255, 255, 255, (alpha * 255.0) as u8]);
+        }
+    }
+    let mut image = Image::new(
+        Extent3d {
+            width: VIGNETTE_SIZE,
+            height: VIGNETTE_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::linear();
+    image
+}
+
+pub fn add_vignette(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        ImageNode {
+            image: images.add(vignette_image()),
+            color: Color::srgba(0.7, 0.0, 0.0, 0.0),
+            ..default()
+        },
+        bevy::picking::Pickable::IGNORE,
+        Name::new("LowHealthVignette"),
+        LowHealthVignette,
+        GameObject,
+    ));
+}
+
+/// Pulses the vignette while the player is low on health.
+pub fn update_vignette(
+    time: Res<Time>,
+    player: Query<&Health, With<PlayerControl>>,
+    mut vignette: Query<&mut ImageNode, With<LowHealthVignette>>,
+) {
+    let Ok(mut image) = vignette.single_mut() else {
+        return;
+    };
+    let low_health = player.single().is_ok_and(|h| h.value <= LOW_HEALTH);
+    let alpha = if low_health {
+        0.6 + 0.2 * (time.elapsed_secs() * 3.0).sin()
+    } else {
+        0.0
+    };
+    image.color.set_alpha(alpha);
+}
@@ -0,0 +1,243 @@
+use bevy::prelude::*;
+
+use super::{
+    extra::button::ButtonReleased,
+    glyphs::{gamepad_button_name, key_name},
+    settings::settings_button,
+};
+use crate::{
+    consts,
+    input::{BindingInput, GamepadBindings, InputAction, KeyBindings},
+    settings::GameSettings,
+};
+
+/// Rebinding screen, spawned on top of the settings.
+#[derive(Component)]
+pub struct ControlsScreen;
+
+#[derive(Component, Clone, Copy)]
+pub enum ControlsButton {
+    Slot(InputAction, usize),
+    GamepadSlot(InputAction),
+    Defaults,
+    Back,
+}
+
+/// Slot waiting for the next pressed key or button, `Escape` cancels it and `Delete` clears the slot.
+#[derive(Component)]
+pub struct Rebinding;
+
+fn slot_label(settings: &GameSettings, button: ControlsButton) -> String {
+    let name = match button {
+        ControlsButton::Slot(action, slot) => settings
+            .bindings
+            .inputs(action)
+            .get(slot)
+            .map(|key| key_name(*key)),
+        ControlsButton::GamepadSlot(action) => settings
+            .gamepad_bindings
+            .inputs(action)
+            .first()
+            .map(|button| gamepad_button_name(*button)),
+        _ => None,
+    };
+    name.unwrap_or_else(|| "-".to_owned())
+}
+
+pub fn spawn_controls(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    settings: &GameSettings,
+    parent: Entity,
+) {
+    let text_style = (
+        TextFont {
+            font: asset_server.load(consts::BASE_FONT),
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Srgba::hex("CFC6B8").unwrap().into()),
+    );
+    commands
+        .spawn((
+            BackgroundColor(Srgba::hex("2b2b2b").unwrap().into()),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            GlobalZIndex(11),
+            Name::new("controls"),
+            ControlsScreen,
+            ChildOf(parent),
+        ))
+        .with_children(|root| {
+            root.spawn((
+                Text::new("Controls"),
+                TextFont {
+                    font: asset_server.load(consts::BASE_FONT),
+                    font_size: 35.0,
+                    ..default()
+                },
+                TextColor(consts::MY_ACCENT_COLOR),
+            ));
+            root.spawn((
+                Text::new(
+                    "Click a slot and press the new key or button, Delete clears it, Escape cancels",
+                ),
+                TextFont {
+                    font: asset_server.load(consts::BASE_FONT),
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(consts::MY_ACCENT_COLOR),
+                Node {
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                },
+            ));
+            for action in InputAction::ALL {
+                root.spawn(Node {
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn((
+                        Text::new(action.description()),
+                        text_style.clone(),
+                        Node {
+                            width: Val::Px(260.0),
+                            ..default()
+                        },
+                    ));
+                    let slots = (0..KeyCode::SLOTS)
+                        .map(|slot| ControlsButton::Slot(action, slot))
+                        .chain([ControlsButton::GamepadSlot(action)]);
+                    for button in slots {
+                        settings_button(
+                            row,
+                            button,
+                            130.0,
+                            &slot_label(settings, button),
+                            &text_style,
+                            on_controls_button,
+                        );
+                    }
+                });
+            }
+            root.spawn(Node {
+                margin: UiRect::top(Val::Px(10.0)),
+                column_gap: Val::Px(15.0),
+                ..default()
+            })
+            .with_children(|row| {
+                for (button, text) in [
+                    (ControlsButton::Defaults, "Defaults"),
+                    (ControlsButton::Back, "Back"),
+                ] {
+                    settings_button(row, button, 150.0, text, &text_style, on_controls_button);
+                }
+            });
+        });
+}
+
+fn refresh_slots(
+    settings: &GameSettings,
+    slots: &Query<(Entity, &ControlsButton, &Children)>,
+    texts: &mut Query<&mut Text>,
+) {
+    for (_, button, children) in slots.iter() {
+        if !matches!(
+            button,
+            ControlsButton::Slot(..) | ControlsButton::GamepadSlot(_)
+        ) {
+            continue;
+        }
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(child) {
+                **text = slot_label(settings, *button);
+            }
+        }
+    }
+}
+
+fn on_controls_button(
+    t: Trigger<ButtonReleased>,
+    mut commands: Commands,
+    buttons: Query<(Entity, &ControlsButton, &Children)>,
+    mut texts: Query<&mut Text>,
+    rebinding: Query<Entity, With<Rebinding>>,
+    screens: Query<Entity, With<ControlsScreen>>,
+    mut settings: ResMut<GameSettings>,
+) {
+    let Ok((entity, button, children)) = buttons.get(t.target()) else {
+        return;
+    };
+    for waiting in rebinding.iter() {
+        commands.entity(waiting).remove::<Rebinding>();
+    }
+    match button {
+        ControlsButton::Slot(..) | ControlsButton::GamepadSlot(_) => {
+            refresh_slots(&settings, &buttons, &mut texts);
+            commands.entity(entity).insert(Rebinding);
+            for child in children.iter() {
+                if let Ok(mut text) = texts.get_mut(child) {
+                    **text = "...".to_owned();
+                }
+            }
+        }
+        ControlsButton::Defaults => {
+            settings.bindings = KeyBindings::default();
+            settings.gamepad_bindings = GamepadBindings::default();
+            refresh_slots(&settings, &buttons, &mut texts);
+        }
+        ControlsButton::Back => {
+            for screen in screens.iter() {
+                commands.entity(screen).despawn();
+            }
+        }
+    }
+}
+
+/// Binds the first key or gamepad button pressed after clicking the slot.
+pub fn capture_input(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    rebinding: Query<(Entity, &ControlsButton), With<Rebinding>>,
+    slots: Query<(Entity, &ControlsButton, &Children)>,
+    mut texts: Query<&mut Text>,
+    mut settings: ResMut<GameSettings>,
+) {
+    let key = keys.get_just_pressed().next().copied();
+    let gamepad_button = gamepads
+        .iter()
+        .find_map(|g| g.get_just_pressed().next().copied());
+    for (entity, button) in rebinding.iter() {
+        match (*button, key, gamepad_button) {
+            (_, Some(KeyCode::Escape), _) => {}
+            (ControlsButton::Slot(action, slot), Some(KeyCode::Delete), _) => {
+                settings.bindings.clear(action, slot)
+            }
+            (ControlsButton::GamepadSlot(action), Some(KeyCode::Delete), _) => {
+                settings.gamepad_bindings.clear(action, 0)
+            }
+            (ControlsButton::Slot(action, slot), Some(key), _) => {
+                settings.bindings.rebind(action, slot, key)
+            }
+            (ControlsButton::GamepadSlot(action), _, Some(gamepad_button)) => {
+                settings.gamepad_bindings.rebind(action, 0, gamepad_button)
+            }
+            // still waiting for the input of the right device
+            _ => continue,
+        }
+        commands.entity(entity).remove::<Rebinding>();
+        refresh_slots(&settings, &slots, &mut texts);
+    }
+}
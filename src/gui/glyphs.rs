@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+
+use crate::consts::{BASE_FONT, MY_ACCENT_COLOR};
+
+/// Keys with their own image, the other keys are drawn as an empty key with their name.
+fn key_image(key: KeyCode) -> Option<&'static str> {
+    Some(match key {
+        KeyCode::ArrowLeft => "ui/keyboard_arrows_left_outline.png",
+        KeyCode::ArrowRight => "ui/keyboard_arrows_right_outline.png",
+        KeyCode::ArrowUp => "ui/keyboard_arrows_up_outline.png",
+        KeyCode::ArrowDown => "ui/keyboard_arrows_down_outline.png",
+        KeyCode::Space => "ui/keyboard_space_outline.png",
+        KeyCode::Escape => "ui/keyboard_escape_outline.png",
+        KeyCode::KeyF => "ui/keyboard_f_outline.png",
+        KeyCode::KeyH => "ui/keyboard_h_outline.png",
+        KeyCode::KeyL => "ui/keyboard_l_outline.png",
+        KeyCode::KeyU => "ui/keyboard_u_outline.png",
+        _ => return None,
+    })
+}
+
+/// Short name of the key, `KeyCode::KeyA` is shown as `A` and `KeyCode::Digit1` as `1`.
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    ["Key", "Digit", "Arrow"]
+        .iter()
+        .find_map(|prefix| name.strip_prefix(prefix).filter(|n| !n.is_empty()))
+        .unwrap_or(&name)
+        .to_owned()
+}
+
+/// Name printed on the button, following the Xbox layout.
+pub fn gamepad_button_name(button: GamepadButton) -> String {
+    match button {
+        GamepadButton::South => "A",
+        GamepadButton::East => "B",
+        GamepadButton::West => "X",
+        GamepadButton::North => "Y",
+        GamepadButton::LeftTrigger => "LB",
+        GamepadButton::RightTrigger => "RB",
+        GamepadButton::LeftTrigger2 => "LT",
+        GamepadButton::RightTrigger2 => "RT",
+        GamepadButton::LeftThumb => "LS",
+        GamepadButton::RightThumb => "RS",
+        GamepadButton::Select => "Back",
+        GamepadButton::Start => "Start",
+        GamepadButton::DPadUp => "D-pad up",
+        GamepadButton::DPadDown => "D-pad down",
+        GamepadButton::DPadLeft => "D-pad left",
+        GamepadButton::DPadRight => "D-pad right",
+        other => return format!("{:?}", other),
+    }
+    .to_owned()
+}
+
+fn spawn_glyph(
+    parent: &mut ChildSpawnerCommands,
+    asset_server: &AssetServer,
+    image: &'static str,
+    label: Option<String>,
+) {
+    let color = MY_ACCENT_COLOR.with_alpha(0.6);
+    let mut glyph = parent.spawn((
+        Node {
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        ImageNode {
+            color,
+            image: asset_server.load(image),
+            ..default()
+        },
+    ));
+    if let Some(label) = label {
+        glyph.with_child((
+            Text::new(label),
+            TextFont {
+                font: asset_server.load(BASE_FONT),
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(color),
+        ));
+    }
+}
+
+pub fn spawn_key_glyph(
+    parent: &mut ChildSpawnerCommands,
+    asset_server: &AssetServer,
+    key: KeyCode,
+) {
+    match key_image(key) {
+        Some(image) => spawn_glyph(parent, asset_server, image, None),
+        None => spawn_glyph(
+            parent,
+            asset_server,
+            "ui/keyboard_outline.png",
+            Some(key_name(key)),
+        ),
+    }
+}
+
+/// D-pad has its own images, the other buttons are drawn as a circle with their name.
+pub fn spawn_gamepad_glyph(
+    parent: &mut ChildSpawnerCommands,
+    asset_server: &AssetServer,
+    button: GamepadButton,
+) {
+    let dpad = match button {
+        GamepadButton::DPadUp => "ui/gamepad_dpad_up_outline.png",
+        GamepadButton::DPadDown => "ui/gamepad_dpad_down_outline.png",
+        GamepadButton::DPadLeft => "ui/gamepad_dpad_left_outline.png",
+        GamepadButton::DPadRight => "ui/gamepad_dpad_right_outline.png",
+        _ => {
+            let label = gamepad_button_name(button);
+            spawn_glyph(
+                parent,
+                asset_server,
+                "ui/gamepad_button_outline.png",
+                Some(label),
+            );
+            return;
+        }
+    };
+    spawn_glyph(parent, asset_server, dpad, None);
+}
@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+
+use crate::board::components::*;
+
+const BAR_WIDTH: f32 = 0.6;
+const BAR_HEIGHT: f32 = 0.08;
+/// Above the head of the billboard, relative to the piece.
+const BAR_OFFSET: f32 = 0.6;
+
+#[derive(Resource)]
+pub struct HealthBarAssets {
+    mesh: Handle<Mesh>,
+    background: Handle<StandardMaterial>,
+    fill: Handle<StandardMaterial>,
+}
+
+/// Bar of the damaged enemy, child of the piece so it turns to the camera with it.
+#[derive(Component)]
+pub struct HealthBar;
+
+#[derive(Component)]
+pub struct HealthBarFill;
+
+pub fn setup_health_bars(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let material = |color: Color| StandardMaterial {
+        base_color: color,
+        unlit: true,
+        double_sided: true,
+        cull_mode: None,
+        ..default()
+    };
+    commands.insert_resource(HealthBarAssets {
+        mesh: meshes.add(Rectangle::new(BAR_WIDTH, BAR_HEIGHT)),
+        background: materials.add(material(Color::srgb(0.1, 0.05, 0.05))),
+        fill: materials.add(material(Color::srgb(0.8, 0.15, 0.15))),
+    });
+}
+
+/// Shows the bars over the enemies that lost some of their health.
+///
+/// The enemies die from the first hit while they spawn with [`crate::rules::ENEMY_HEALTH`] of 1,
+/// so no bar is shown until the monsters get more health or the attacks less damage.
+pub fn sync_health_bars(
+    mut commands: Commands,
+    bar_assets: Res<HealthBarAssets>,
+    enemies: Query<
+        (Entity, &Health, &MaxHealth, Option<&Children>),
+        (With<AiControl>, Changed<Health>),
+    >,
+    bars: Query<&Children, With<HealthBar>>,
+    mut fills: Query<&mut Transform, With<HealthBarFill>>,
+    mut died: RemovedComponents<Health>,
+    children: Query<&Children>,
+    is_bar: Query<(), With<HealthBar>>,
+) {
+    for entity in died.read() {
+        for child in children.get(entity).into_iter().flatten() {
+            if is_bar.contains(*child) {
+                commands.entity(*child).despawn();
+            }
+        }
+    }
+    for (entity, health, max, piece_children) in enemies.iter() {
+        let bar = piece_children
+            .into_iter()
+            .flatten()
+            .find(|c| is_bar.contains(**c))
+            .copied();
+        if health.value >= **max {
+            if let Some(bar) = bar {
+                commands.entity(bar).despawn();
+            }
+            continue;
+        }
+        let fraction = health.value as f32 / (**max).max(1) as f32;
+        let fill_transform = Transform::from_xyz(-BAR_WIDTH * (1.0 - fraction) / 2.0, 0.0, 0.001)
+            .with_scale(Vec3::new(fraction, 1.0, 1.0));
+        let existing_fill = bar
+            .and_then(|b| bars.get(b).ok())
+            .and_then(|c| c.iter().find(|c| fills.contains(*c)));
+        if let Some(fill) = existing_fill {
+            if let Ok(mut transform) = fills.get_mut(fill) {
+                *transform = fill_transform;
+            }
+            continue;
+        }
+        commands.entity(entity).with_child((
+            Mesh3d(bar_assets.mesh.clone()),
+            MeshMaterial3d(bar_assets.background.clone()),
+            Transform::from_xyz(0.0, BAR_OFFSET, 0.0),
+            HealthBar,
+            children![(
+                Mesh3d(bar_assets.mesh.clone()),
+                MeshMaterial3d(bar_assets.fill.clone()),
+                fill_transform,
+                HealthBarFill,
+            )],
+        ));
+    }
+}
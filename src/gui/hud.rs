@@ -0,0 +1,173 @@
+use bevy::{prelude::*, window::WindowResized};
+
+use crate::{
+    board::components::*,
+    consts::{self, MY_ACCENT_COLOR},
+    rules,
+    states::TurnCounter,
+    ImageAssets,
+};
+
+const HEART_SIZE: f32 = 24.0;
+const FONT_SIZE: f32 = 16.0;
+/// Window height the HUD is designed for, it grows and shrinks with the window.
+const REFERENCE_HEIGHT: f32 = 720.0;
+
+/// Multiplier of the HUD sizes following the window height.
+#[derive(Resource, Reflect)]
+pub struct HudScale(pub f32);
+
+impl Default for HudScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+#[derive(Component)]
+pub struct HudHearts;
+
+#[derive(Component, Clone, Copy)]
+pub enum HudField {
+    Depth,
+    Turn,
+    Kills,
+    Seed,
+}
+
+pub fn add_hud(mut commands: Commands, asset_server: Res<AssetServer>, scale: Res<HudScale>) {
+    let font = TextFont {
+        font: asset_server.load(consts::BASE_FONT),
+        font_size: FONT_SIZE * scale.0,
+        ..default()
+    };
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                top: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::End,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            bevy::picking::Pickable::IGNORE,
+            Name::new("Hud"),
+            GameObject,
+        ))
+        .with_children(|root| {
+            root.spawn((
+                Node {
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(2.0),
+                    ..default()
+                },
+                HudHearts,
+            ));
+            for field in [
+                HudField::Depth,
+                HudField::Turn,
+                HudField::Kills,
+                HudField::Seed,
+            ] {
+                root.spawn((
+                    Text::new(""),
+                    font.clone(),
+                    TextColor(MY_ACCENT_COLOR),
+                    field,
+                ));
+            }
+        });
+}
+
+/// Full hearts for the health left, empty ones for the lost health.
+pub fn update_hearts(
+    mut commands: Commands,
+    assets: Res<ImageAssets>,
+    scale: Res<HudScale>,
+    player: Query<(Ref<Health>, &MaxHealth), With<PlayerControl>>,
+    hearts: Query<Entity, With<HudHearts>>,
+    added: Query<(), Added<HudHearts>>,
+    mut died: RemovedComponents<Health>,
+) {
+    let died = died.read().count() > 0;
+    let health = player.single().ok();
+    let changed = health.as_ref().is_some_and(|(h, _)| h.is_changed());
+    if !changed && !died && added.is_empty() && !scale.is_changed() {
+        return;
+    }
+    let Ok(row) = hearts.single() else {
+        return;
+    };
+    let (value, max) = health.map_or((0, rules::PLAYER_HEALTH), |(h, max)| (h.value, **max));
+    let size = Val::Px(HEART_SIZE * scale.0);
+    commands
+        .entity(row)
+        .despawn_related::<Children>()
+        .with_children(|row| {
+            for i in 0..value.max(max) {
+                let index = if i < value {
+                    consts::HEART
+                } else {
+                    consts::EMPTY_HEART
+                };
+                row.spawn((
+                    ImageNode::from_atlas_image(
+                        assets.image_transparent.clone(),
+                        TextureAtlas {
+                            layout: assets.layout.clone(),
+                            index,
+                        },
+                    ),
+                    Node {
+                        width: size,
+                        height: size,
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+pub fn update_fields(
+    mut fields: Query<(&mut Text, &HudField)>,
+    added: Query<(), Added<HudField>>,
+    depth: Res<Depth>,
+    turn: Res<TurnCounter>,
+    seed: Res<RunSeed>,
+    dead_enemies: Query<(), (With<AiControl>, Without<Piece>)>,
+    mut died: RemovedComponents<Piece>,
+    revived: Query<(), Added<Piece>>,
+) {
+    let kills_changed = died.read().count() > 0 || !revived.is_empty();
+    if added.is_empty()
+        && !kills_changed
+        && !depth.is_changed()
+        && !turn.is_changed()
+        && !seed.is_changed()
+    {
+        return;
+    }
+    for (mut text, field) in fields.iter_mut() {
+        **text = match field {
+            HudField::Depth => format!("Depth {}", **depth),
+            HudField::Turn => format!("Turn {}", **turn),
+            HudField::Kills => format!("Kills {}", dead_enemies.iter().count()),
+            HudField::Seed => format!("Seed {}", **seed),
+        };
+    }
+}
+
+pub fn scale_hud(
+    mut resized: EventReader<WindowResized>,
+    mut scale: ResMut<HudScale>,
+    mut fonts: Query<&mut TextFont, With<HudField>>,
+) {
+    let Some(event) = resized.read().last() else {
+        return;
+    };
+    scale.0 = (event.height / REFERENCE_HEIGHT).clamp(0.75, 3.0);
+    for mut font in fonts.iter_mut() {
+        font.font_size = FONT_SIZE * scale.0;
+    }
+}
@@ -0,0 +1,146 @@
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+use bevy_third_person_camera::ThirdPersonCamera;
+use leafwing_input_manager::action_state::ActionState;
+
+use crate::{
+    board::components::GameObject,
+    consts::{self, MY_ACCENT_COLOR},
+    input::InputAction,
+    messages::{MessageCategory, MessageLog},
+};
+
+/// Messages shown when the history is closed.
+const RECENT_MESSAGES: usize = 6;
+const LINE_HEIGHT: f32 = 20.0;
+
+/// The whole history is shown and can be scrolled with the mouse wheel.
+#[derive(Resource, Reflect, Default)]
+pub struct MessageHistoryOpen(pub bool);
+
+#[derive(Component)]
+pub struct MessageList;
+
+fn category_color(category: MessageCategory) -> Color {
+    match category {
+        MessageCategory::Combat => MY_ACCENT_COLOR,
+        MessageCategory::Danger => Color::srgb_u8(214, 69, 65),
+        MessageCategory::Item => Color::srgb_u8(230, 190, 90),
+        MessageCategory::Discovery => Color::srgb_u8(110, 170, 210),
+        MessageCategory::Info => Color::srgb_u8(159, 111, 97),
+    }
+}
+
+pub fn add_message_log(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                width: Val::Px(420.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            BackgroundColor(Color::BLACK.with_alpha(0.4)),
+            Name::new("MessageLog"),
+            GameObject,
+        ))
+        .with_child((
+            Node {
+                flex_direction: FlexDirection::Column,
+                width: Val::Percent(100.0),
+                max_height: Val::Px(LINE_HEIGHT * RECENT_MESSAGES as f32),
+                overflow: Overflow::scroll_y(),
+                ..default()
+            },
+            ScrollPosition::default(),
+            MessageList,
+        ));
+}
+
+pub fn toggle_history(
+    input: Query<&ActionState<InputAction>>,
+    mut history: ResMut<MessageHistoryOpen>,
+    mut camera: Query<&mut ThirdPersonCamera>,
+) {
+    let Ok(action_state) = input.single() else {
+        return;
+    };
+    if !action_state.just_pressed(&InputAction::Log) {
+        return;
+    }
+    history.0 = !history.0;
+    // the mouse wheel scrolls the history instead of zooming
+    for mut camera in camera.iter_mut() {
+        camera.zoom_enabled = !history.0;
+    }
+}
+
+pub fn update_message_list(
+    mut commands: Commands,
+    log: Res<MessageLog>,
+    history: Res<MessageHistoryOpen>,
+    asset_server: Res<AssetServer>,
+    mut list: Query<(Entity, &mut Node, &mut ScrollPosition), With<MessageList>>,
+) {
+    if !log.is_changed() && !history.is_changed() {
+        return;
+    }
+    let Ok((entity, mut node, mut scroll)) = list.single_mut() else {
+        return;
+    };
+    let amount = if history.0 {
+        node.max_height = Val::Vh(60.0);
+        log.messages.len()
+    } else {
+        node.max_height = Val::Px(LINE_HEIGHT * RECENT_MESSAGES as f32);
+        RECENT_MESSAGES
+    };
+    // newest messages are at the bottom, the layout clamps the offset
+    scroll.offset_y = f32::MAX;
+    let font = TextFont {
+        font: asset_server.load(consts::BASE_FONT),
+        font_size: 14.0,
+        ..default()
+    };
+    commands
+        .entity(entity)
+        .despawn_related::<Children>()
+        .with_children(|list| {
+            for message in log.recent(amount) {
+                list.spawn((
+                    Text::new(message.text.clone()),
+                    font.clone(),
+                    TextColor(category_color(message.category)),
+                    Node {
+                        min_height: Val::Px(LINE_HEIGHT),
+                        flex_shrink: 0.0,
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+pub fn scroll_history(
+    mut wheel: EventReader<MouseWheel>,
+    history: Res<MessageHistoryOpen>,
+    mut list: Query<&mut ScrollPosition, With<MessageList>>,
+) {
+    if !history.0 {
+        wheel.clear();
+        return;
+    }
+    for event in wheel.read() {
+        let lines = match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / LINE_HEIGHT,
+        };
+        for mut scroll in list.iter_mut() {
+            scroll.offset_y = (scroll.offset_y - lines * LINE_HEIGHT).max(0.0);
+        }
+    }
+}
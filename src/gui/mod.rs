@@ -2,15 +2,24 @@ use crate::{
     board::components::*,
     consts::{BASE_FONT, MY_ACCENT_COLOR},
     despawn_recursive_by_component,
-    input::InputAction,
-    states::*,
+    input::{InputAction, InputDevice},
+    settings::GameSettings,
+    states::{practice::PracticeMode, *},
 };
 use bevy::prelude::*;
 use leafwing_input_manager::action_state::ActionState;
 
 pub mod button_anim;
+mod combat_feedback;
+mod controls;
 mod death_screen;
 pub mod extra;
+pub mod glyphs;
+mod health_bars;
+mod hud;
+mod message_log;
+pub mod settings;
+pub mod tooltip;
 
 #[derive(Component, Reflect)]
 pub struct CurrentActorInfoRoot;
@@ -41,7 +50,21 @@ impl Plugin for GameGuiPlugin {
         app.register_type::<CurrentActorInfo>()
             .register_type::<ActionInfo>()
             .insert_resource(HelpDisplayEnabled(true))
-            .add_systems(OnEnter(MainGameState::Game), add_actor_info)
+            .init_resource::<combat_feedback::ScreenShake>()
+            .register_type::<message_log::MessageHistoryOpen>()
+            .init_resource::<message_log::MessageHistoryOpen>()
+            .register_type::<hud::HudScale>()
+            .init_resource::<hud::HudScale>()
+            .add_systems(
+                OnEnter(MainGameState::Game),
+                (
+                    add_actor_info,
+                    combat_feedback::add_vignette,
+                    message_log::add_message_log,
+                    hud::add_hud,
+                    tooltip::add_tooltip,
+                ),
+            )
             .add_systems(
                 OnEnter(GameTurnSteps::ActionSelection),
                 update_info.after(PreparingActions::TrimWrongMoves),
@@ -51,32 +74,57 @@ impl Plugin for GameGuiPlugin {
                 despawn_recursive_by_component::<ActionInfo>,
             )
             .add_observer(on_action_info_added)
+            .add_observer(settings::open_settings)
             .add_systems(
                 Update,
                 (
                     death_screen::create_death_screen,
                     insert_compass,
-                    switch_help_ui,
+                    switch_help_ui.run_if(settings::settings_closed),
+                    refresh_action_info,
                     update_compass_pos,
+                    combat_feedback::spawn_damage_numbers,
+                    combat_feedback::move_damage_numbers,
+                    combat_feedback::update_vignette,
+                    message_log::toggle_history.run_if(settings::settings_closed),
+                    message_log::update_message_list,
+                    message_log::scroll_history,
+                    hud::scale_hud,
+                    hud::update_hearts.after(hud::scale_hud),
+                    hud::update_fields,
+                    health_bars::sync_health_bars,
+                    tooltip::update_tooltip,
                 )
                     .run_if(in_state(MainGameState::Game)),
+            )
+            .add_systems(
+                Update,
+                controls::capture_input.run_if(any_with_component::<controls::Rebinding>),
+            )
+            .add_systems(Startup, health_bars::setup_health_bars)
+            .add_systems(
+                PostUpdate,
+                combat_feedback::shake_camera
+                    .before(bevy::transform::TransformSystem::TransformPropagate),
             );
     }
 }
 
 fn switch_help_ui(
     input: Query<&ActionState<InputAction>>,
-    mut help: ResMut<HelpDisplayEnabled>,
+    help: Res<HelpDisplayEnabled>,
+    mut settings: ResMut<GameSettings>,
     mut q: Query<&mut Visibility, With<ActionInfo>>,
 ) {
-    let Ok(action_state) = input.single() else {
-        return;
-    };
-
-    if !action_state.just_pressed(&InputAction::Hide) {
+    if let Ok(action_state) = input.single() {
+        if action_state.just_pressed(&InputAction::Hide) {
+            settings.show_help = !settings.show_help;
+        }
+    }
+    // the setting reaches the help resource in the next frame
+    if !help.is_changed() {
         return;
     }
-    help.0 = !help.0;
     for mut vis in q.iter_mut() {
         *vis = if help.0 {
             Visibility::Inherited
@@ -130,6 +178,7 @@ fn update_info(
     mut q: Query<(&mut Text, &ChildOf), With<CurrentActorInfo>>,
     q2: Query<(&PossibleActions, Option<&PlayerControl>, &Piece), With<CurrentActorToken>>,
     help: Res<HelpDisplayEnabled>,
+    practice: Res<PracticeMode>,
 ) {
     let Ok((mut t, parent)) = q.single_mut() else {
         return;
@@ -151,14 +200,21 @@ fn update_info(
                 description: format!("{:?}", a.action_type()),
             });
         }
-        p.spawn(ActionInfo {
-            action: InputAction::Space,
-            description: "Switch camera movement".to_owned(),
-        });
-        p.spawn(ActionInfo {
-            action: InputAction::Hide,
-            description: "Hide this info".to_owned(),
-        });
+        for action in [
+            InputAction::Space,
+            InputAction::Undo,
+            InputAction::Pause,
+            InputAction::Log,
+            InputAction::Hide,
+        ] {
+            if action == InputAction::Undo && !practice.0 {
+                continue;
+            }
+            p.spawn(ActionInfo {
+                action,
+                description: action.description().to_owned(),
+            });
+        }
     });
 }
 
@@ -176,28 +232,56 @@ fn on_action_info_added(
     trigger: Trigger<OnAdd, ActionInfo>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    settings: Res<GameSettings>,
+    device: Res<InputDevice>,
     q: Query<&ActionInfo>,
 ) {
     let Ok(info) = q.get(trigger.target()) else {
         return;
     };
-    commands.entity(trigger.target()).with_children(|r| {
-        let img = match info.action {
-            InputAction::Left => "ui/keyboard_arrows_left_outline.png".to_owned(),
-            InputAction::Right => "ui/keyboard_arrows_right_outline.png".to_owned(),
-            InputAction::Up => "ui/keyboard_arrows_up_outline.png".to_owned(),
-            InputAction::Down => "ui/keyboard_arrows_down_outline.png".to_owned(),
-            InputAction::Space => "ui/keyboard_space_outline.png".to_owned(),
-            InputAction::Hide => "ui/keyboard_h_outline.png".to_owned(),
-        };
-        r.spawn((
-            Node::default(),
-            ImageNode {
-                color: MY_ACCENT_COLOR.with_alpha(0.6),
-                image: asset_server.load(img),
-                ..default()
-            },
-        ));
+    let mut entity = commands.entity(trigger.target());
+    fill_action_info(&mut entity, info, &asset_server, &settings, *device);
+}
+
+/// Glyphs follow the device used last and the bindings.
+fn refresh_action_info(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<GameSettings>,
+    device: Res<InputDevice>,
+    q: Query<(Entity, &ActionInfo)>,
+) {
+    if !settings.is_changed() && !device.is_changed() {
+        return;
+    }
+    for (entity, info) in q.iter() {
+        let mut entity = commands.entity(entity);
+        entity.despawn_related::<Children>();
+        fill_action_info(&mut entity, info, &asset_server, &settings, *device);
+    }
+}
+
+fn fill_action_info(
+    entity: &mut EntityCommands,
+    info: &ActionInfo,
+    asset_server: &AssetServer,
+    settings: &GameSettings,
+    device: InputDevice,
+) {
+    entity.with_children(|r| {
+        // unbound actions are listed without the glyph
+        match device {
+            InputDevice::Keyboard => {
+                if let Some(key) = settings.bindings.inputs(info.action).first() {
+                    glyphs::spawn_key_glyph(r, asset_server, *key);
+                }
+            }
+            InputDevice::Gamepad => {
+                if let Some(button) = settings.gamepad_bindings.inputs(info.action).first() {
+                    glyphs::spawn_gamepad_glyph(r, asset_server, *button);
+                }
+            }
+        }
         r.spawn((
             TextFont {
                 font: asset_server.load(BASE_FONT),
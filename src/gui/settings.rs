@@ -0,0 +1,280 @@
+use bevy::{ecs::system::IntoObserverSystem, prelude::*};
+
+use super::{controls, extra::button::ButtonReleased};
+use crate::{consts, settings::GameSettings, ObserverExtension};
+
+/// Opens the settings on top of the targeted menu, they are closed together with it.
+#[derive(Event)]
+pub struct OpenSettings;
+
+#[derive(Component)]
+pub struct SettingsScreen;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SettingField {
+    CameraDistance,
+    CameraSensitivity,
+    FogStart,
+    FogEnd,
+    TorchShadows,
+    UiScale,
+    AnimationSpeed,
+    Help,
+    WaitForAnimations,
+}
+
+const FIELDS: [SettingField; 9] = [
+    SettingField::CameraDistance,
+    SettingField::CameraSensitivity,
+    SettingField::FogStart,
+    SettingField::FogEnd,
+    SettingField::TorchShadows,
+    SettingField::UiScale,
+    SettingField::AnimationSpeed,
+    SettingField::Help,
+    SettingField::WaitForAnimations,
+];
+
+fn on_off(value: bool) -> String {
+    if value { "On" } else { "Off" }.to_owned()
+}
+
+impl SettingField {
+    fn label(&self) -> &'static str {
+        match self {
+            SettingField::CameraDistance => "Camera distance",
+            SettingField::CameraSensitivity => "Camera sensitivity",
+            SettingField::FogStart => "Fog start",
+            SettingField::FogEnd => "Fog end",
+            SettingField::TorchShadows => "Torch shadows",
+            SettingField::UiScale => "UI scale",
+            SettingField::AnimationSpeed => "Animation speed",
+            SettingField::Help => "Help",
+            SettingField::WaitForAnimations => "Wait for animations",
+        }
+    }
+
+    fn value(&self, settings: &GameSettings) -> String {
+        match self {
+            SettingField::CameraDistance => format!("{:.1}", settings.camera_distance),
+            SettingField::CameraSensitivity => format!("{:.2}", settings.camera_sensitivity),
+            SettingField::FogStart => format!("{:.1}", settings.fog_start),
+            SettingField::FogEnd => format!("{:.1}", settings.fog_end),
+            SettingField::TorchShadows => on_off(settings.torch_shadows),
+            SettingField::UiScale => format!("{:.0}%", settings.ui_scale * 100.0),
+            SettingField::AnimationSpeed => format!("{:.2}x", settings.animation_speed),
+            SettingField::Help => on_off(settings.show_help),
+            SettingField::WaitForAnimations => on_off(settings.wait_for_animations),
+        }
+    }
+
+    /// Moves the value one step up or down, switches have only two values so both flip them.
+    fn change(&self, settings: &mut GameSettings, up: bool) {
+        let step = if up { 1.0 } else { -1.0 };
+        match self {
+            SettingField::CameraDistance => settings.camera_distance += 0.5 * step,
+            SettingField::CameraSensitivity => settings.camera_sensitivity += 0.25 * step,
+            SettingField::FogStart => settings.fog_start += 0.5 * step,
+            SettingField::FogEnd => settings.fog_end += 0.5 * step,
+            SettingField::TorchShadows => settings.torch_shadows = !settings.torch_shadows,
+            SettingField::UiScale => settings.ui_scale += 0.1 * step,
+            SettingField::AnimationSpeed => settings.animation_speed += 0.25 * step,
+            SettingField::Help => settings.show_help = !settings.show_help,
+            SettingField::WaitForAnimations => {
+                settings.wait_for_animations = !settings.wait_for_animations
+            }
+        }
+        settings.clamp();
+    }
+}
+
+#[derive(Component, Clone, Copy)]
+pub enum SettingsButton {
+    Decrease(SettingField),
+    Increase(SettingField),
+    Controls,
+    Defaults,
+    Back,
+}
+
+/// Text showing the current value of the setting.
+#[derive(Component)]
+pub struct SettingValue(SettingField);
+
+/// Game is not paused and unpaused by the keys pressed in the settings.
+pub fn settings_closed(screens: Query<(), With<SettingsScreen>>) -> bool {
+    screens.is_empty()
+}
+
+pub(super) fn settings_button<B: Component, M>(
+    commands: &mut ChildSpawnerCommands,
+    button: B,
+    width: f32,
+    text: &str,
+    text_style: &(TextFont, TextColor),
+    observer: impl IntoObserverSystem<ButtonReleased, (), M>,
+) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Px(width),
+                height: Val::Px(36.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Button,
+            BackgroundColor::from(Srgba::hex("4F6F52").unwrap()),
+            Name::new(format!("button:{}", text)),
+            button,
+        ))
+        .observe_in_child(observer)
+        .with_child((Text::new(text), text_style.clone()));
+}
+
+pub fn open_settings(
+    trigger: Trigger<OpenSettings>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    settings: Res<GameSettings>,
+) {
+    let text_style = (
+        TextFont {
+            font: asset_server.load(consts::BASE_FONT),
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Srgba::hex("CFC6B8").unwrap().into()),
+    );
+    let mut root = commands.spawn((
+        BackgroundColor(Srgba::hex("2b2b2b").unwrap().into()),
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(8.0),
+            ..default()
+        },
+        GlobalZIndex(10),
+        Name::new("settings"),
+        SettingsScreen,
+    ));
+    if trigger.target() != Entity::PLACEHOLDER {
+        root.insert(ChildOf(trigger.target()));
+    }
+    root.with_children(|root| {
+        root.spawn((
+            Text::new("Settings"),
+            TextFont {
+                font: asset_server.load(consts::BASE_FONT),
+                font_size: 35.0,
+                ..default()
+            },
+            TextColor(consts::MY_ACCENT_COLOR),
+            Node {
+                margin: UiRect::bottom(Val::Px(10.0)),
+                ..default()
+            },
+        ));
+        for field in FIELDS {
+            root.spawn(Node {
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(8.0),
+                ..default()
+            })
+            .with_children(|row| {
+                row.spawn((
+                    Text::new(field.label()),
+                    text_style.clone(),
+                    Node {
+                        width: Val::Px(240.0),
+                        ..default()
+                    },
+                ));
+                settings_button(
+                    row,
+                    SettingsButton::Decrease(field),
+                    36.0,
+                    "<",
+                    &text_style,
+                    on_settings_button,
+                );
+                row.spawn((
+                    Text::new(field.value(&settings)),
+                    text_style.clone(),
+                    TextLayout::new_with_justify(JustifyText::Center),
+                    Node {
+                        width: Val::Px(90.0),
+                        ..default()
+                    },
+                    SettingValue(field),
+                ));
+                settings_button(
+                    row,
+                    SettingsButton::Increase(field),
+                    36.0,
+                    ">",
+                    &text_style,
+                    on_settings_button,
+                );
+            });
+        }
+        root.spawn(Node {
+            margin: UiRect::top(Val::Px(10.0)),
+            column_gap: Val::Px(15.0),
+            ..default()
+        })
+        .with_children(|row| {
+            for (button, text) in [
+                (SettingsButton::Controls, "Controls"),
+                (SettingsButton::Defaults, "Defaults"),
+                (SettingsButton::Back, "Back"),
+            ] {
+                settings_button(row, button, 150.0, text, &text_style, on_settings_button);
+            }
+        });
+    });
+}
+
+fn on_settings_button(
+    t: Trigger<ButtonReleased>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    buttons: Query<&SettingsButton>,
+    mut values: Query<(&mut Text, &SettingValue)>,
+    screens: Query<Entity, With<SettingsScreen>>,
+    mut settings: ResMut<GameSettings>,
+) {
+    let Ok(button) = buttons.get(t.target()) else {
+        return;
+    };
+    match *button {
+        SettingsButton::Decrease(field) => field.change(&mut settings, false),
+        SettingsButton::Increase(field) => field.change(&mut settings, true),
+        SettingsButton::Controls => {
+            if let Some(screen) = screens.iter().next() {
+                controls::spawn_controls(&mut commands, &asset_server, &settings, screen);
+            }
+            return;
+        }
+        // the controls have their own defaults
+        SettingsButton::Defaults => {
+            *settings = GameSettings {
+                bindings: settings.bindings.clone(),
+                ..default()
+            }
+        }
+        SettingsButton::Back => {
+            for screen in screens.iter() {
+                commands.entity(screen).despawn();
+            }
+            return;
+        }
+    }
+    for (mut text, value) in values.iter_mut() {
+        **text = value.0.value(&settings);
+    }
+}
@@ -0,0 +1,147 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_third_person_camera::ThirdPersonCamera;
+
+use crate::{
+    board::{components::*, renderer, state::BoardQuery},
+    consts::{self, MY_ACCENT_COLOR},
+    rules::BoardState,
+    vectors::Vector2Int,
+};
+
+/// Distance between the cursor and the tooltip.
+const CURSOR_OFFSET: f32 = 16.0;
+
+#[derive(Component)]
+pub struct Tooltip;
+
+pub fn add_tooltip(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font: asset_server.load(consts::BASE_FONT),
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(MY_ACCENT_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.7)),
+        Visibility::Hidden,
+        bevy::picking::Pickable::IGNORE,
+        Name::new("Tooltip"),
+        Tooltip,
+        GameObject,
+    ));
+}
+
+fn tile_name(tile: &TileType) -> &'static str {
+    match tile {
+        TileType::None => "Wall",
+        TileType::BaseFloor => "Floor",
+        TileType::Pit => "Pit",
+        TileType::Door { open: true } => "Open door",
+        TileType::Door { open: false } => "Closed door",
+        TileType::LockedDoor => "Locked door",
+        TileType::Spikes => "Spikes",
+        TileType::Lava => "Lava",
+        TileType::Water => "Water",
+        TileType::Ice => "Ice",
+        TileType::CrackedWall => "Cracked wall",
+    }
+}
+
+fn trap_name(kind: TrapKind) -> &'static str {
+    match kind {
+        TrapKind::PressurePlate => "Pressure plate",
+        TrapKind::Dart => "Dart trap",
+        TrapKind::Teleport => "Teleport trap",
+        TrapKind::Alarm => "Alarm trap",
+    }
+}
+
+/// Tile under the point of the screen, found by casting the ray from the camera onto the floor plane.
+pub fn floor_tile(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    cursor: Vec2,
+) -> Option<Vector2Int> {
+    let ray = camera.viewport_to_world(camera_transform, cursor).ok()?;
+    let distance = ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Y))?;
+    let point = ray.get_point(distance);
+    Some(Vector2Int::new(
+        point.x.round() as i32,
+        point.z.round() as i32,
+    ))
+}
+
+/// Describes the hovered tile, only the tiles the player can see are described.
+pub fn update_tooltip(
+    window: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<ThirdPersonCamera>>,
+    mut tooltip: Query<(&mut Text, &mut Node, &mut Visibility), With<Tooltip>>,
+    player: Query<&PiecePos, With<PlayerControl>>,
+    pieces: Query<(
+        &Piece,
+        Option<&Health>,
+        Option<&MaxHealth>,
+        Has<Burning>,
+        Has<Awake>,
+        Has<Flying>,
+    )>,
+    occupancy: Res<Occupancy>,
+    board: BoardQuery,
+    current: Res<CurrentBoard>,
+) {
+    let Ok((mut text, mut node, mut visibility)) = tooltip.single_mut() else {
+        return;
+    };
+    let (Ok(window), Ok((camera, camera_transform)), Ok(player)) =
+        (window.single(), camera.single(), player.single())
+    else {
+        return;
+    };
+    let hovered = window
+        .cursor_position()
+        .and_then(|cursor| floor_tile(camera, camera_transform, cursor))
+        .filter(|pos| renderer::visibility(&board, player.0, *pos) == Visibility::Inherited);
+    let (Some(pos), Some(cursor)) = (hovered, window.cursor_position()) else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+    let Some(tile) = board.tile(pos) else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+    let mut lines = vec![tile_name(tile).to_owned()];
+    if let Some(trap) = board.trap(pos).filter(|t| t.discovered) {
+        lines.push(trap_name(trap.kind).to_owned());
+    }
+    if let Some(item) = current.items.get(&pos) {
+        lines.push(format!("{:?}", item));
+    }
+    let piece = occupancy.get(pos).and_then(|e| pieces.get(e).ok());
+    if let Some((piece, health, max, burning, awake, flying)) = piece {
+        lines.push(format!("{:?}", piece));
+        if let Some(health) = health {
+            let max = max.map_or(health.value, |m| **m);
+            lines.push(format!("Health {}/{}", health.value, max));
+        }
+        let statuses: Vec<&str> = [(burning, "burning"), (awake, "awake"), (flying, "flying")]
+            .into_iter()
+            .filter_map(|(has, name)| has.then_some(name))
+            .collect();
+        if !statuses.is_empty() {
+            lines.push(statuses.join(", "));
+        }
+    }
+    let description = lines.join("\n");
+    if **text != description {
+        **text = description;
+    }
+    node.left = Val::Px(cursor.x + CURSOR_OFFSET);
+    node.top = Val::Px(cursor.y + CURSOR_OFFSET);
+    visibility.set_if_neq(Visibility::Inherited);
+}
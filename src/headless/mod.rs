@@ -0,0 +1,208 @@
+//! Game running without a window or rendering, driven by injected player input.
+//!
+//! Used by the simulation and by the scenario tests.
+use bevy::{prelude::*, state::app::StatesPlugin};
+use leafwing_input_manager::action_state::ActionState;
+
+use crate::{
+    actions::ActionType,
+    board::{components::*, BoardPlugin},
+    input::InputAction,
+    states::{
+        CurrentActorToken, GameStatesPlugin, GameTurnSteps, MainGameState, PendingActions,
+        PossibleActions,
+    },
+    vectors::Vector2Int,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Safety limit for games where the player never gets the turn back.
+const MAX_FRAMES_PER_TURN: usize = 200;
+
+/// Action the player can take in the current turn.
+pub struct PlayerOption {
+    pub action: ActionType,
+    pub target: Option<Vector2Int>,
+    pub key: Option<InputAction>,
+}
+
+pub struct HeadlessGame {
+    pub app: App,
+}
+
+impl HeadlessGame {
+    /// Game on the board generated from the seed.
+    pub fn from_seed(seed: u64) -> Self {
+        let mut app = Self::app();
+        app.insert_resource(RunSeed(seed));
+        Self::start(app)
+    }
+
+    /// Game on a hand-written board, pieces are spawned on its spawn points.
+    pub fn from_board(board: CurrentBoard) -> Self {
+        let mut app = Self::app();
+        app.insert_resource(RunSeed(0)).insert_resource(board);
+        Self::start(app)
+    }
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, BoardPlugin, GameStatesPlugin));
+        app
+    }
+
+    fn start(mut app: App) -> Self {
+        app.finish();
+        app.cleanup();
+        app.world_mut()
+            .resource_mut::<NextState<MainGameState>>()
+            .set(MainGameState::Game);
+        // enter the game and spawn the pieces
+        app.update();
+        let mut game = Self { app };
+        game.run_until_player_turn();
+        game
+    }
+
+    pub fn world(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    pub fn player(&mut self) -> Option<Entity> {
+        let world = self.world();
+        world
+            .query_filtered::<Entity, (With<PlayerControl>, With<Piece>)>()
+            .iter(world)
+            .next()
+    }
+
+    pub fn player_pos(&mut self) -> Option<Vector2Int> {
+        let player = self.player()?;
+        self.world().get::<PiecePos>(player).map(|p| p.0)
+    }
+
+    /// Health of the player, zero after the death.
+    pub fn player_health(&mut self) -> u32 {
+        self.player()
+            .and_then(|e| self.world().get::<Health>(e))
+            .map_or(0, |h| h.value)
+    }
+
+    /// Living piece standing on the tile.
+    pub fn piece_at(&mut self, pos: Vector2Int) -> Option<(Piece, u32)> {
+        let world = self.world();
+        let entity = world.resource::<Occupancy>().get(pos)?;
+        let entity = world.get_entity(entity).ok()?;
+        Some((
+            entity.get::<Piece>()?.clone(),
+            entity.get::<Health>()?.value,
+        ))
+    }
+
+    pub fn tile(&self, pos: Vector2Int) -> Option<TileType> {
+        self.app
+            .world()
+            .get_resource::<CurrentBoard>()?
+            .tiles
+            .get(&pos)
+            .cloned()
+    }
+
+    /// Trap lying on the tile, armed or not.
+    pub fn trap(&mut self, pos: Vector2Int) -> Option<Trap> {
+        let world = self.world();
+        world
+            .query::<(&PiecePos, &Trap)>()
+            .iter(world)
+            .find(|(p, _)| p.0 == pos)
+            .map(|(_, trap)| trap.clone())
+    }
+
+    pub fn enemies_alive(&mut self) -> usize {
+        let world = self.world();
+        world
+            .query::<&Piece>()
+            .iter(world)
+            .filter(|p| *p != &Piece::Player)
+            .count()
+    }
+
+    pub fn is_player_turn(&mut self) -> bool {
+        let world = self.world();
+        let selecting = world
+            .get_resource::<State<GameTurnSteps>>()
+            .is_some_and(|s| s.get() == &GameTurnSteps::ActionSelection);
+        selecting
+            && world
+                .query_filtered::<(), (With<CurrentActorToken>, With<PlayerControl>)>()
+                .iter(world)
+                .next()
+                .is_some()
+    }
+
+    /// Steps frames until the player can select the action, `false` if that never happens.
+    pub fn run_until_player_turn(&mut self) -> bool {
+        for _ in 0..MAX_FRAMES_PER_TURN {
+            if self.is_player_turn() {
+                return true;
+            }
+            if self.player().is_none() {
+                return false;
+            }
+            self.app.update();
+        }
+        false
+    }
+
+    /// Actions left after trimming the wrong moves.
+    pub fn player_options(&mut self) -> Vec<PlayerOption> {
+        let world = self.world();
+        world
+            .query_filtered::<&PossibleActions, (With<CurrentActorToken>, With<PlayerControl>)>()
+            .iter(world)
+            .flat_map(|actions| actions.iter())
+            .map(|a| PlayerOption {
+                action: a.action_type(),
+                target: a.target_pos(),
+                key: a.get_input(),
+            })
+            .collect()
+    }
+
+    /// Presses and releases the key in the player's turn, then plays until the next one.
+    ///
+    /// Returns `false` when the key does not match any of the possible actions.
+    pub fn press(&mut self, key: InputAction) -> bool {
+        if !self.run_until_player_turn() {
+            return false;
+        }
+        let Some(player) = self.player() else {
+            return false;
+        };
+        self.with_input(player, |input| {
+            input.press(&key);
+            input.release(&key);
+        });
+        self.app.update();
+        self.with_input(player, |input| input.reset_all());
+        if self.world().resource::<PendingActions>().is_empty() {
+            return false;
+        }
+        // leave the current turn before waiting for the next one
+        self.app.update();
+        self.run_until_player_turn();
+        true
+    }
+
+    fn with_input(&mut self, player: Entity, f: impl FnOnce(&mut ActionState<InputAction>)) {
+        let mut entity = self.world().entity_mut(player);
+        if !entity.contains::<ActionState<InputAction>>() {
+            entity.insert(ActionState::<InputAction>::default());
+        }
+        if let Some(mut input) = entity.get_mut::<ActionState<InputAction>>() {
+            f(&mut input);
+        }
+    }
+}
@@ -0,0 +1,308 @@
+//! Scenarios playing full turns on hand-written boards.
+use super::HeadlessGame;
+use crate::{
+    board::components::{
+        Awake, Bombs, Burning, CurrentBoard, Health, Item, Keys, Occupancy, Piece, TileType,
+        TrapIndex, TrapKind,
+    },
+    input::InputAction,
+    messages::{MessageCategory, MessageLog},
+    states::{practice::PracticeMode, replay::ActionLog, TurnCounter},
+    vectors::Vector2Int,
+};
+
+// the player always starts at 1x1
+const ROOM_WITH_ENEMY: &str = "fffff\nfPEff\nfffff";
+const ROOM_WITH_PIT: &str = "fffff\nfPpff\nfffff";
+const EMPTY_ROOM: &str = "fffff\nfPfff\nfffff";
+const CORRIDOR_WITH_DOOR: &str = "fffff\nfPdff\nfffff";
+const CORRIDOR_WITH_LOCKED_DOOR: &str = "fffff\nkPlff\nfffff";
+const CORRIDOR_WITH_CRACKED_WALL: &str = "fffff\nbPcff\nfffff";
+const ROOM_WITH_SPIKES: &str = "fffff\nfP^ff\nfffff";
+const ROOM_WITH_LAVA: &str = "fffff\nfP%ff\nfffff";
+const ROOM_WITH_WATER: &str = "fffff\nfP~ff\nfffff";
+const ICE_TO_THE_EDGE: &str = "fffff\nfP___\nfffff";
+const ICE_TO_THE_LAVA: &str = "fffff\nfP_%f\nfffff";
+
+const ROOM_WITH_FAR_ENEMY: &str = "fffffffffffff\nfPffffffffffE\nfffffffffffff";
+
+fn game(level: &str) -> HeadlessGame {
+    HeadlessGame::from_board(CurrentBoard::parse(level).unwrap())
+}
+
+/// Game with a hidden trap right next to the player.
+fn game_with_trap(level: &str, kind: TrapKind) -> HeadlessGame {
+    let mut board = CurrentBoard::parse(level).unwrap();
+    board.traps.insert(Vector2Int::new(2, 1), kind);
+    HeadlessGame::from_board(board)
+}
+
+fn enemy_at(game: &mut HeadlessGame, pos: Vector2Int) -> bevy::prelude::Entity {
+    game.world()
+        .resource::<Occupancy>()
+        .get(pos)
+        .expect("enemy not spawned")
+}
+
+#[test]
+fn walk() {
+    let mut game = game(EMPTY_ROOM);
+    assert!(game.press(InputAction::Left));
+    assert_eq!(game.player_pos(), Some(Vector2Int::new(2, 1)));
+    assert!(game.press(InputAction::Up));
+    assert_eq!(game.player_pos(), Some(Vector2Int::new(2, 2)));
+}
+
+#[test]
+fn walk_into_pit() {
+    let mut game = game(ROOM_WITH_PIT);
+    assert!(!game.press(InputAction::Left), "move accepted");
+    assert_eq!(game.player_pos(), Some(Vector2Int::new(1, 1)));
+}
+
+#[test]
+fn kill_enemy() {
+    let mut game = game(ROOM_WITH_ENEMY);
+    game.press(InputAction::Left);
+    assert_eq!(game.piece_at(Vector2Int::new(2, 1)), None);
+    assert_eq!(game.enemies_alive(), 0);
+    assert_eq!(game.player_health(), 3);
+}
+
+#[test]
+fn log_the_fight() {
+    let mut game = game(ROOM_WITH_ENEMY);
+    game.press(InputAction::Left);
+    let messages: Vec<(MessageCategory, String)> = game
+        .world()
+        .resource::<MessageLog>()
+        .messages
+        .iter()
+        .map(|m| (m.category, m.text.clone()))
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            (
+                MessageCategory::Combat,
+                "You hit the enemy for 1".to_owned(),
+            ),
+            (MessageCategory::Combat, "The enemy dies".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn get_hit() {
+    let mut game = game(ROOM_WITH_ENEMY);
+    let enemy = enemy_at(&mut game, Vector2Int::new(2, 1));
+    game.world().get_mut::<Health>(enemy).unwrap().value = 5;
+    // the enemy acts at least once in two turns of the player
+    game.press(InputAction::Left);
+    game.press(InputAction::Left);
+    assert_eq!(
+        game.piece_at(Vector2Int::new(2, 1)),
+        Some((Piece::Enemy, 3))
+    );
+    assert!(game.player_health() < 3, "player was not hit");
+}
+
+#[test]
+fn open_door() {
+    let mut game = game(CORRIDOR_WITH_DOOR);
+    let door = Vector2Int::new(2, 1);
+    assert!(game.press(InputAction::Left), "door not opened");
+    assert_eq!(game.tile(door), Some(TileType::Door { open: true }));
+    assert_eq!(game.player_pos(), Some(Vector2Int::new(1, 1)));
+    assert!(game.press(InputAction::Left), "move not accepted");
+    assert_eq!(game.player_pos(), Some(door));
+}
+
+#[test]
+fn open_locked_door() {
+    let mut game = game(CORRIDOR_WITH_LOCKED_DOOR);
+    let door = Vector2Int::new(2, 1);
+    assert!(!game.press(InputAction::Left), "door opened without key");
+    // pick up the key
+    game.press(InputAction::Right);
+    game.press(InputAction::Left);
+    assert!(game.press(InputAction::Left), "door not opened");
+    assert_eq!(game.tile(door), Some(TileType::Door { open: true }));
+    let keys = game
+        .player()
+        .and_then(|e| game.world().get::<Keys>(e))
+        .map(|k| k.0);
+    assert_eq!(keys, Some(0));
+}
+
+#[test]
+fn step_on_spikes() {
+    let mut game = game(ROOM_WITH_SPIKES);
+    assert!(game.press(InputAction::Left));
+    assert_eq!(game.player_health(), 2);
+}
+
+#[test]
+fn walk_into_lava() {
+    let mut game = game(ROOM_WITH_LAVA);
+    assert!(!game.press(InputAction::Left), "move accepted");
+    assert_eq!(game.player_health(), 3);
+}
+
+#[test]
+fn slide_on_ice() {
+    let mut to_edge = game(ICE_TO_THE_EDGE);
+    to_edge.press(InputAction::Left);
+    assert_eq!(to_edge.player_pos(), Some(Vector2Int::new(4, 1)));
+    // sliding pieces do not stop before the hazards
+    let mut into_lava = game(ICE_TO_THE_LAVA);
+    into_lava.press(InputAction::Left);
+    assert_eq!(into_lava.player_health(), 0);
+}
+
+#[test]
+fn put_out_fire() {
+    let mut game = game(ROOM_WITH_WATER);
+    let player = game.player().unwrap();
+    game.world().entity_mut(player).insert(Burning(3));
+    game.press(InputAction::Left);
+    // burns once more before reaching the water
+    assert_eq!(game.player_health(), 2);
+    assert!(
+        game.world().get::<Burning>(player).is_none(),
+        "still burning"
+    );
+}
+
+#[test]
+fn step_on_dart_trap() {
+    let mut game = game_with_trap(EMPTY_ROOM, TrapKind::Dart);
+    assert!(game.press(InputAction::Left));
+    assert_eq!(game.player_health(), 2);
+    let trap = game.trap(Vector2Int::new(2, 1)).expect("trap not spawned");
+    assert!(trap.discovered, "trap not discovered");
+    assert!(trap.armed, "trap disarmed");
+}
+
+#[test]
+fn search_and_disarm_trap() {
+    let mut game = game_with_trap(EMPTY_ROOM, TrapKind::PressurePlate);
+    let trap = Vector2Int::new(2, 1);
+    assert!(game.press(InputAction::Search), "search not accepted");
+    assert_eq!(game.trap(trap).map(|t| t.discovered), Some(true));
+    // walking into the known trap disarms it
+    assert!(game.press(InputAction::Left), "disarm not accepted");
+    assert_eq!(game.player_pos(), Some(Vector2Int::new(1, 1)));
+    assert_eq!(game.trap(trap).map(|t| t.armed), Some(false));
+    assert!(game.press(InputAction::Left), "move not accepted");
+    assert_eq!(game.player_health(), 3);
+}
+
+#[test]
+fn index_follows_the_traps() {
+    let mut game = game_with_trap(EMPTY_ROOM, TrapKind::Dart);
+    let pos = Vector2Int::new(2, 1);
+    let trap = game.world().resource::<TrapIndex>().get(pos);
+    assert!(trap.is_some(), "trap not indexed");
+    game.world().despawn(trap.unwrap());
+    assert_eq!(game.world().resource::<TrapIndex>().get(pos), None);
+    assert!(game.press(InputAction::Left), "move not accepted");
+    assert_eq!(game.player_health(), 3);
+}
+
+#[test]
+fn step_on_teleport_trap() {
+    let mut game = game_with_trap(EMPTY_ROOM, TrapKind::Teleport);
+    let trap = Vector2Int::new(2, 1);
+    game.press(InputAction::Left);
+    let pos = game.player_pos();
+    assert!(
+        pos.is_some_and(|p| p != trap),
+        "player position is {:?}",
+        pos
+    );
+}
+
+#[test]
+fn set_off_alarm() {
+    let mut game = game_with_trap(ROOM_WITH_FAR_ENEMY, TrapKind::Alarm);
+    let enemy = enemy_at(&mut game, Vector2Int::new(12, 1));
+    game.press(InputAction::Left);
+    assert!(game.world().get::<Awake>(enemy).is_some(), "enemy asleep");
+    // the far enemy walks towards the player
+    game.press(InputAction::Right);
+    assert_eq!(game.piece_at(Vector2Int::new(12, 1)), None);
+}
+
+#[test]
+fn blow_up_cracked_wall() {
+    let mut game = game(CORRIDOR_WITH_CRACKED_WALL);
+    let wall = Vector2Int::new(2, 1);
+    assert!(!game.press(InputAction::Left), "wall blown up without bomb");
+    // pick up the bomb
+    game.press(InputAction::Right);
+    game.press(InputAction::Left);
+    assert!(game.press(InputAction::Left), "wall not blown up");
+    assert_eq!(game.tile(wall), Some(TileType::BaseFloor));
+    let bombs = game
+        .player()
+        .and_then(|e| game.world().get::<Bombs>(e))
+        .map(|b| b.0);
+    assert_eq!(bombs, Some(0));
+    assert!(game.press(InputAction::Left), "move not accepted");
+    assert_eq!(game.player_pos(), Some(wall));
+}
+
+#[test]
+fn undo_brings_back_the_enemy_and_the_key() {
+    let mut game = game("fffff\nkPEff\nfffff");
+    game.world().insert_resource(PracticeMode(true));
+    let enemy_pos = Vector2Int::new(2, 1);
+    let key_pos = Vector2Int::new(0, 1);
+    let enemy = enemy_at(&mut game, enemy_pos);
+    // the turns are recorded from the next one
+    game.press(InputAction::Search);
+    assert!(game.press(InputAction::Left), "attack not accepted");
+    assert_eq!(game.enemies_alive(), 0);
+    assert!(game.press(InputAction::Right), "move not accepted");
+    assert_eq!(
+        game.world().resource::<CurrentBoard>().items.get(&key_pos),
+        None
+    );
+    for _ in 0..2 {
+        game.press(InputAction::Undo);
+        // the restored turn starts again
+        game.app.update();
+        game.run_until_player_turn();
+    }
+    assert_eq!(game.player_pos(), Some(Vector2Int::new(1, 1)));
+    assert_eq!(game.piece_at(enemy_pos), Some((Piece::Enemy, 1)));
+    assert_eq!(
+        game.world().resource::<Occupancy>().get(enemy_pos),
+        Some(enemy)
+    );
+    assert_eq!(
+        game.world().resource::<CurrentBoard>().items.get(&key_pos),
+        Some(&Item::Key)
+    );
+    let player = game.player().unwrap();
+    assert_eq!(game.world().get::<Keys>(player).map(|k| **k), Some(0));
+}
+
+#[test]
+fn undo_removes_the_turn_from_the_replay() {
+    let mut game = game(EMPTY_ROOM);
+    game.world().insert_resource(PracticeMode(true));
+    game.press(InputAction::Left);
+    game.press(InputAction::Left);
+    let turn = **game.world().resource::<TurnCounter>();
+    let logged = game.world().resource::<ActionLog>().actions.len();
+    game.press(InputAction::Left);
+    game.press(InputAction::Undo);
+    // the restored turn starts again
+    game.app.update();
+    game.run_until_player_turn();
+    assert_eq!(game.player_pos(), Some(Vector2Int::new(3, 1)));
+    assert_eq!(**game.world().resource::<TurnCounter>(), turn);
+    assert_eq!(game.world().resource::<ActionLog>().actions.len(), logged);
+}
@@ -1,10 +1,12 @@
 use bevy::prelude::*;
+use bevy_third_person_camera::ThirdPersonCamera;
 use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::board::components::PlayerControl;
+use crate::{board::components::PlayerControl, settings::GameSettings};
 
 /// This is the list of "things in the game I want to be able to do based on input"
-#[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect)]
+#[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect, Serialize, Deserialize)]
 pub enum InputAction {
     Left,
     Right,
@@ -12,6 +14,159 @@ pub enum InputAction {
     Down,
     Space,
     Hide,
+    Undo,
+    Search,
+    Log,
+    Pause,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 10] = [
+        InputAction::Up,
+        InputAction::Down,
+        InputAction::Left,
+        InputAction::Right,
+        InputAction::Space,
+        InputAction::Hide,
+        InputAction::Undo,
+        InputAction::Search,
+        InputAction::Log,
+        InputAction::Pause,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            InputAction::Left => "Left",
+            InputAction::Right => "Right",
+            InputAction::Up => "Up",
+            InputAction::Down => "Down",
+            InputAction::Space => "Switch camera movement",
+            InputAction::Hide => "Show/hide help",
+            InputAction::Undo => "Undo last turn",
+            InputAction::Search => "Search",
+            InputAction::Log => "Message history",
+            InputAction::Pause => "Pause",
+        }
+    }
+}
+
+/// Input that can be bound to the actions from the settings.
+pub trait BindingInput: Buttonlike + Copy + PartialEq {
+    /// Every action can be bound to that many inputs
+    const SLOTS: usize;
+    fn defaults(action: InputAction) -> Vec<Self>;
+}
+
+impl BindingInput for KeyCode {
+    const SLOTS: usize = 2;
+    fn defaults(action: InputAction) -> Vec<Self> {
+        match action {
+            InputAction::Left => vec![KeyCode::ArrowLeft, KeyCode::KeyA],
+            InputAction::Right => vec![KeyCode::ArrowRight, KeyCode::KeyD],
+            InputAction::Up => vec![KeyCode::ArrowUp, KeyCode::KeyW],
+            InputAction::Down => vec![KeyCode::ArrowDown, KeyCode::KeyS],
+            InputAction::Space => vec![KeyCode::Space],
+            InputAction::Hide => vec![KeyCode::KeyH],
+            InputAction::Undo => vec![KeyCode::KeyU, KeyCode::Backspace],
+            InputAction::Search => vec![KeyCode::KeyF],
+            InputAction::Log => vec![KeyCode::KeyL],
+            InputAction::Pause => vec![KeyCode::Escape],
+        }
+    }
+}
+
+/// The left stick moves the player too, it is not rebindable.
+///
+/// The game has no wait, push or inventory actions, the face buttons get the closest ones instead:
+/// South searches in place of waiting, the others undo, open the history and show the help.
+impl BindingInput for GamepadButton {
+    const SLOTS: usize = 1;
+    fn defaults(action: InputAction) -> Vec<Self> {
+        vec![match action {
+            InputAction::Left => GamepadButton::DPadLeft,
+            InputAction::Right => GamepadButton::DPadRight,
+            InputAction::Up => GamepadButton::DPadUp,
+            InputAction::Down => GamepadButton::DPadDown,
+            InputAction::Space => GamepadButton::RightTrigger,
+            InputAction::Hide => GamepadButton::West,
+            InputAction::Undo => GamepadButton::East,
+            InputAction::Search => GamepadButton::South,
+            InputAction::Log => GamepadButton::North,
+            InputAction::Pause => GamepadButton::Start,
+        }]
+    }
+}
+
+/// Inputs bound to the actions, the first input of the action is shown in the help panel.
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Bindings<T>(pub Vec<(InputAction, Vec<T>)>);
+
+pub type KeyBindings = Bindings<KeyCode>;
+pub type GamepadBindings = Bindings<GamepadButton>;
+
+impl<T: BindingInput> Default for Bindings<T> {
+    fn default() -> Self {
+        Self(
+            InputAction::ALL
+                .iter()
+                .map(|a| (*a, T::defaults(*a)))
+                .collect(),
+        )
+    }
+}
+
+impl<T: BindingInput> Bindings<T> {
+    pub fn inputs(&self, action: InputAction) -> &[T] {
+        self.0
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map_or(&[], |(_, inputs)| inputs.as_slice())
+    }
+
+    /// Actions missing in the saved bindings, for example added in a newer version, get their defaults.
+    pub fn add_missing(&mut self) {
+        for action in InputAction::ALL {
+            if !self.0.iter().any(|(a, _)| *a == action) {
+                self.0.push((action, T::defaults(action)));
+            }
+        }
+    }
+
+    /// Replaces the input in the slot of the action, the input is taken away from any other action.
+    pub fn rebind(&mut self, action: InputAction, slot: usize, input: T) {
+        self.clear(action, slot);
+        for (_, inputs) in self.0.iter_mut() {
+            inputs.retain(|i| *i != input);
+        }
+        if let Some((_, inputs)) = self.0.iter_mut().find(|(a, _)| *a == action) {
+            inputs.insert(slot.min(inputs.len()), input);
+            inputs.truncate(T::SLOTS);
+        }
+    }
+
+    pub fn clear(&mut self, action: InputAction, slot: usize) {
+        if let Some((_, inputs)) = self.0.iter_mut().find(|(a, _)| *a == action) {
+            if slot < inputs.len() {
+                inputs.remove(slot);
+            }
+        }
+    }
+
+    fn add_to(&self, input_map: &mut InputMap<InputAction>) {
+        for (action, inputs) in self.0.iter() {
+            for input in inputs {
+                input_map.insert(*action, *input);
+            }
+        }
+    }
+}
+
+/// Device used last, the help panel shows its glyphs.
+#[derive(Resource, Reflect, Default, Clone, Copy, PartialEq)]
+pub enum InputDevice {
+    #[default]
+    Keyboard,
+    Gamepad,
 }
 
 pub struct InputPlugin;
@@ -19,23 +174,69 @@ pub struct InputPlugin;
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(InputManagerPlugin::<InputAction>::default())
-            .add_systems(Update, add_input_bindings);
-    }
-}
-
-fn add_input_bindings(query: Query<Entity, Added<PlayerControl>>, mut commands: Commands) {
-    let input_map = InputMap::new([
-        (InputAction::Left, KeyCode::KeyA),
-        (InputAction::Left, KeyCode::ArrowLeft),
-        (InputAction::Right, KeyCode::KeyD),
-        (InputAction::Right, KeyCode::ArrowRight),
-        (InputAction::Up, KeyCode::KeyW),
-        (InputAction::Up, KeyCode::ArrowUp),
-        (InputAction::Down, KeyCode::KeyS),
-        (InputAction::Down, KeyCode::ArrowDown),
-        (InputAction::Hide, KeyCode::KeyH),
-    ]);
-    for entity in query.iter() {
+            .register_type::<InputDevice>()
+            .init_resource::<InputDevice>()
+            .add_systems(
+                Update,
+                (add_input_bindings, detect_input_device, toggle_camera_lock),
+            );
+    }
+}
+
+/// New players get the bindings from the settings, existing ones are updated when they change.
+fn add_input_bindings(
+    added: Query<Entity, Added<PlayerControl>>,
+    players: Query<Entity, With<PlayerControl>>,
+    settings: Res<GameSettings>,
+    mut commands: Commands,
+) {
+    let mut input_map = InputMap::default();
+    settings.bindings.add_to(&mut input_map);
+    settings.gamepad_bindings.add_to(&mut input_map);
+    input_map
+        .insert(InputAction::Left, GamepadControlDirection::LEFT_LEFT)
+        .insert(InputAction::Right, GamepadControlDirection::LEFT_RIGHT)
+        .insert(InputAction::Up, GamepadControlDirection::LEFT_UP)
+        .insert(InputAction::Down, GamepadControlDirection::LEFT_DOWN);
+    let entities = if settings.is_changed() {
+        players.iter().collect::<Vec<_>>()
+    } else {
+        added.iter().collect()
+    };
+    for entity in entities {
         commands.entity(entity).insert(input_map.clone());
     }
 }
+
+/// Stick has to be pushed that far to count as the gamepad being used.
+const STICK_THRESHOLD: f32 = 0.5;
+
+fn detect_input_device(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    mut device: ResMut<InputDevice>,
+) {
+    if keys.get_just_pressed().next().is_some() || mouse.get_just_pressed().next().is_some() {
+        device.set_if_neq(InputDevice::Keyboard);
+    } else if gamepads.iter().any(|g| {
+        g.get_just_pressed().next().is_some()
+            || g.left_stick().length() > STICK_THRESHOLD
+            || g.right_stick().length() > STICK_THRESHOLD
+    }) {
+        device.set_if_neq(InputDevice::Gamepad);
+    }
+}
+
+/// Replaces the camera key toggle, so it follows the bindings and works with the gamepad.
+fn toggle_camera_lock(
+    input: Query<&ActionState<InputAction>>,
+    mut camera: Query<&mut ThirdPersonCamera>,
+) {
+    if !input.iter().any(|a| a.just_pressed(&InputAction::Space)) {
+        return;
+    }
+    for mut camera in camera.iter_mut() {
+        camera.cursor_lock_active = !camera.cursor_lock_active;
+    }
+}
@@ -0,0 +1,2 @@
+//! Parts of the game that do not need the app, shared with the benchmarks.
+pub mod vectors;
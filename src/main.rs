@@ -10,6 +10,8 @@ use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
 use bevy_sprite3d::Sprite3dPlugin;
 use bevy_third_person_camera::*;
+use bevy_tweening::TweeningPlugin;
+use rougepush::vectors;
 
 mod actions;
 mod board;
@@ -19,10 +21,16 @@ mod debug;
 mod dungeon;
 mod gfx;
 mod gui;
+#[cfg(not(target_arch = "wasm32"))]
+mod headless;
 mod input;
 mod lights;
+mod messages;
+mod rules;
+mod settings;
+#[cfg(not(target_arch = "wasm32"))]
+mod simulation;
 mod states;
-mod vectors;
 
 #[derive(Resource, AssetCollection)]
 struct ImageAssets {
@@ -64,16 +72,28 @@ impl ObserverExtension for EntityCommands<'_> {
 fn main() {
     #[cfg(target_arch = "wasm32")]
     console_error_panic_hook::set_once();
+    #[cfg(not(target_arch = "wasm32"))]
+    if std::env::args().any(|a| a == "--simulate") {
+        simulation::run();
+        return;
+    }
     let mut app = App::new();
     app.add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
         .add_plugins((
             gfx::GfxPlugin,
             Sprite3dPlugin,
+            TweeningPlugin,
             ThirdPersonCameraPlugin,
             board::BoardPlugin,
+            board::BoardRenderPlugin,
+            board::level::LevelPlugin,
             input::InputPlugin,
             lights::LightsPlugin,
             states::GameStatesPlugin,
+            states::menu::MenuPlugin,
+            states::pause::PausePlugin,
+            states::click_move::ClickMovePlugin,
+            settings::SettingsPlugin,
             gui::GameGuiPlugin,
         ))
         .add_systems(Startup, setup)
@@ -112,23 +132,31 @@ pub fn despawn_recursive_by_component<T: bevy::prelude::Component>(
     }
 }
 
-fn setup(mut commands: Commands) {
+fn setup(mut commands: Commands, settings: Res<settings::GameSettings>) {
     // camera
     commands.spawn((
         ThirdPersonCamera {
-            cursor_lock_key: KeyCode::Space,
-            cursor_lock_toggle_enabled: true,
+            // toggled by `InputAction::Space`, so it follows the bindings
+            cursor_lock_toggle_enabled: false,
             cursor_lock_active: false,
+            // the d-pad moves the player
+            gamepad_settings: CustomGamepadSettings {
+                zoom_in_button: GamepadButton::RightTrigger2,
+                zoom_out_button: GamepadButton::LeftTrigger2,
+                mouse_orbit_button: GamepadButton::LeftTrigger,
+                ..default()
+            },
             zoom_enabled: true,
-            zoom: Zoom::new(4.5, 7.0),
-            zoom_sensitivity: 1.0,
+            zoom: Zoom::new(settings::CAMERA_MIN_ZOOM, settings.camera_distance),
+            zoom_sensitivity: settings.camera_sensitivity,
+            mouse_sensitivity: settings.camera_sensitivity,
             ..default()
         },
         DistanceFog {
             color: consts::BG_COLOR,
             falloff: FogFalloff::Linear {
-                start: 5.0,
-                end: 11.5,
+                start: settings.fog_start,
+                end: settings.fog_end,
             },
             ..default()
         },
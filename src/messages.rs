@@ -0,0 +1,86 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{board::components::PlayerControl, states::TurnCounter};
+
+/// Messages older than that are dropped from the history.
+const HISTORY_SIZE: usize = 200;
+
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MessageCategory {
+    /// Attacks of the player and enemies dying.
+    Combat,
+    /// Something hurts the player.
+    Danger,
+    Item,
+    Discovery,
+    Info,
+}
+
+#[derive(Reflect, Clone, Debug)]
+pub struct Message {
+    pub turn: u64,
+    pub category: MessageCategory,
+    pub text: String,
+}
+
+/// What happened in the current game, the newest message is the last one.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct MessageLog {
+    pub messages: VecDeque<Message>,
+}
+
+impl MessageLog {
+    pub fn push(&mut self, turn: u64, category: MessageCategory, text: impl Into<String>) {
+        if self.messages.len() >= HISTORY_SIZE {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(Message {
+            turn,
+            category,
+            text: text.into(),
+        });
+    }
+
+    pub fn recent(&self, amount: usize) -> impl Iterator<Item = &Message> {
+        self.messages
+            .iter()
+            .skip(self.messages.len().saturating_sub(amount))
+    }
+}
+
+/// Adds the message to the log of the world, if there is one.
+pub fn log(world: &mut World, category: MessageCategory, text: impl Into<String>) {
+    let turn = world.get_resource::<TurnCounter>().map_or(0, |t| **t);
+    if let Some(mut log) = world.get_resource_mut::<MessageLog>() {
+        log.push(turn, category, text);
+    }
+}
+
+pub fn is_player(world: &World, piece: Entity) -> bool {
+    world.get::<PlayerControl>(piece).is_some()
+}
+
+/// Subject of the message about the piece, "You" for the player.
+pub fn piece_name(world: &World, piece: Entity) -> &'static str {
+    if is_player(world, piece) {
+        "You"
+    } else {
+        "The enemy"
+    }
+}
+
+/// Picks the verb form matching the subject from [`piece_name`].
+pub fn verb<'a>(world: &World, piece: Entity, you: &'a str, other: &'a str) -> &'a str {
+    if is_player(world, piece) {
+        you
+    } else {
+        other
+    }
+}
+
+pub fn clear_log(mut log: ResMut<MessageLog>) {
+    log.messages.clear();
+}
@@ -0,0 +1,129 @@
+//! Plain data of the board, the ECS turns these types into components.
+use bevy::reflect::Reflect;
+
+#[derive(Reflect, Default, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+pub enum TileType {
+    #[default]
+    None,
+    BaseFloor,
+    Pit,
+    Door {
+        open: bool,
+    },
+    /// Door that needs a key to be opened, it stays open after that
+    LockedDoor,
+    /// Hurts the pieces walking over it
+    Spikes,
+    /// Kills the pieces that cannot fly over it
+    Lava,
+    /// Slows the pieces down and puts out the fire
+    Water,
+    /// Pieces entering it slide until they hit something
+    Ice,
+    /// Wall that can be blown up with a bomb
+    CrackedWall,
+}
+
+impl TileType {
+    /// Cost of entering the tile for the path finder, `None` if it cannot be entered.
+    pub fn move_cost(&self, is_flying: bool) -> Option<u32> {
+        match (self, is_flying) {
+            (TileType::None, _) => None,
+            (TileType::Pit, false) => None,
+            (TileType::Pit, true) => Some(1),
+            (TileType::BaseFloor, _) => Some(1),
+            (TileType::Door { open }, _) => open.then_some(1),
+            (TileType::LockedDoor | TileType::CrackedWall, _) => None,
+            (TileType::Lava, false) => None,
+            // walkers avoid the hazards when they can
+            (TileType::Spikes, false) => Some(3),
+            (TileType::Water, false) => Some(2),
+            (TileType::Spikes | TileType::Lava | TileType::Water | TileType::Ice, _) => Some(1),
+        }
+    }
+
+    /// Nothing can be pushed or slide into the tile.
+    pub fn blocks_movement(&self) -> bool {
+        matches!(
+            self,
+            TileType::None
+                | TileType::CrackedWall
+                | TileType::Door { open: false }
+                | TileType::LockedDoor
+        )
+    }
+
+    pub fn blocks_sight(&self) -> bool {
+        matches!(
+            self,
+            TileType::None
+                | TileType::CrackedWall
+                | TileType::Door { open: false }
+                | TileType::LockedDoor
+        )
+    }
+
+    /// Tile is rendered as the walls of its neighbours.
+    pub fn is_wall(&self) -> bool {
+        matches!(self, TileType::None | TileType::CrackedWall)
+    }
+
+    /// Tile is rendered with the floor under it.
+    pub fn has_floor(&self) -> bool {
+        !matches!(self, TileType::None | TileType::CrackedWall | TileType::Pit)
+    }
+}
+
+/// Things lying on the board that can be picked up by walking over them.
+#[derive(Reflect, PartialEq, Eq, Clone, Debug)]
+pub enum Item {
+    Key,
+    Bomb,
+}
+
+#[derive(Reflect, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum TrapKind {
+    /// Drops the stones on the piece, works once
+    PressurePlate,
+    /// Shoots the piece each time it is stepped on
+    Dart,
+    /// Moves the piece to a random place of the floor
+    Teleport,
+    /// Wakes up all of the monsters on the floor, works once
+    Alarm,
+}
+
+impl TrapKind {
+    pub fn single_use(&self) -> bool {
+        matches!(self, TrapKind::PressurePlate | TrapKind::Alarm)
+    }
+}
+
+/// Trap hidden on the tile until it is found by the player or triggered.
+#[derive(Reflect, Clone, Debug, PartialEq)]
+pub struct Trap {
+    pub kind: TrapKind,
+    pub discovered: bool,
+    pub armed: bool,
+}
+
+impl Trap {
+    pub fn new(kind: TrapKind) -> Self {
+        Self {
+            kind,
+            discovered: false,
+            armed: true,
+        }
+    }
+
+    /// Player knows about the trap and can disarm it.
+    pub fn is_known(&self) -> bool {
+        self.discovered && self.armed
+    }
+}
+
+#[derive(Reflect, PartialEq, Eq, PartialOrd, Ord, Clone, Debug)]
+pub enum Piece {
+    Player,
+    Enemy,
+}
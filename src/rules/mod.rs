@@ -0,0 +1,289 @@
+//! Game rules that do not depend on the ECS world.
+//!
+//! Systems and actions read the board through a [`BoardState`] implementation
+//! and only apply the results, so the rules can be checked on plain data.
+use std::collections::VecDeque;
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{actions::ActionType, vectors::Vector2Int};
+
+mod board;
+#[cfg(test)]
+mod tests;
+
+pub use board::{Item, Piece, TileType, Trap, TrapKind};
+
+/// Health the player starts the run with.
+pub const PLAYER_HEALTH: u32 = 3;
+pub const ENEMY_HEALTH: u32 = 1;
+/// Damage dealt by every piece that can attack.
+pub const MELEE_DAMAGE: u32 = 1;
+/// How far the monsters look for the player until they are woken up.
+pub const ENEMY_PATH_DISTANCE: usize = 5;
+/// Actors further away from the player than this do not take turns.
+pub const ACTIVE_DISTANCE: i32 = 5;
+pub const SPIKES_DAMAGE: u32 = 1;
+/// Turns a burning piece takes damage in.
+pub const BURNING_TURNS: u32 = 3;
+pub const BURNING_DAMAGE: u32 = 1;
+/// Turns lost by the piece stepping into the water.
+pub const WATER_DELAY: u64 = 2;
+pub const PRESSURE_PLATE_DAMAGE: u32 = 2;
+pub const DART_DAMAGE: u32 = 1;
+/// Searching finds all of the traps this close.
+pub const SEARCH_RADIUS: i32 = 2;
+/// Chance of noticing a trap next to the player after each move.
+pub const PERCEPTION_CHANCE: f64 = 0.25;
+/// How far the monsters woken up by the alarm look for the player.
+pub const AWAKE_PATH_DISTANCE: usize = 30;
+
+/// Read access to the board needed by the rules.
+pub trait BoardState {
+    type Id: Copy + Eq;
+
+    fn tile(&self, pos: Vector2Int) -> Option<&TileType>;
+    /// Piece standing on the tile, if any.
+    fn occupant(&self, pos: Vector2Int) -> Option<Self::Id>;
+    /// Type of the piece if it is alive and can be hit.
+    fn living_piece(&self, id: Self::Id) -> Option<&Piece>;
+    /// Armed trap lying on the tile, hidden or not.
+    fn trap(&self, pos: Vector2Int) -> Option<&Trap>;
+}
+
+/// Walking is allowed only on the free tiles that can be entered without flying.
+///
+/// Known traps are disarmed instead of walking into them.
+pub fn can_walk(board: &impl BoardState, target: Vector2Int) -> bool {
+    board
+        .tile(target)
+        .is_some_and(|t| t.move_cost(false).is_some())
+        && board.occupant(target).is_none()
+        && !board.trap(target).is_some_and(|t| t.is_known())
+}
+
+/// Cracked walls are blown up with a bomb.
+pub fn can_blast(board: &impl BoardState, target: Vector2Int, bombs: u32) -> bool {
+    bombs > 0 && board.tile(target) == Some(&TileType::CrackedWall)
+}
+
+pub fn can_disarm(board: &impl BoardState, target: Vector2Int) -> bool {
+    board.trap(target).is_some_and(|t| t.is_known())
+}
+
+/// Turn the piece starts with, the player always moves first.
+pub fn first_turn(piece: &Piece) -> u64 {
+    match piece {
+        Piece::Player => 0,
+        Piece::Enemy => 1,
+    }
+}
+
+pub fn trap_damage(kind: TrapKind) -> u32 {
+    match kind {
+        TrapKind::PressurePlate => PRESSURE_PLATE_DAMAGE,
+        TrapKind::Dart => DART_DAMAGE,
+        TrapKind::Teleport | TrapKind::Alarm => 0,
+    }
+}
+
+/// Free floor tile the teleport trap on `trap_pos` moves the piece to.
+pub fn teleport_target<'a>(
+    board: &impl BoardState,
+    tiles: impl Iterator<Item = (&'a Vector2Int, &'a TileType)>,
+    seed: u64,
+    turn: u64,
+    trap_pos: Vector2Int,
+) -> Option<Vector2Int> {
+    let mut targets: Vec<Vector2Int> = tiles
+        .filter(|(pos, tile)| {
+            **tile == TileType::BaseFloor && can_walk(board, **pos) && board.trap(**pos).is_none()
+        })
+        .map(|(pos, _)| *pos)
+        .collect();
+    // the board is a hash map, keep the order the same for the replays
+    targets.sort();
+    targets
+        .choose(&mut event_rng(seed, turn, trap_pos))
+        .copied()
+}
+
+/// Random generator for the event on the tile, the same in every replay of the run.
+pub fn event_rng(seed: u64, turn: u64, pos: Vector2Int) -> StdRng {
+    let pos = ((pos.x as u32 as u64) << 32) | pos.y as u32 as u64;
+    StdRng::seed_from_u64(seed ^ turn.rotate_left(17) ^ pos.rotate_left(31))
+}
+
+/// Closed doors open freely, locked ones need a key.
+pub fn can_open(board: &impl BoardState, target: Vector2Int, keys: u32) -> bool {
+    match board.tile(target) {
+        Some(TileType::Door { open }) => !open,
+        Some(TileType::LockedDoor) => keys > 0,
+        _ => false,
+    }
+}
+
+/// What happens to the piece that enters the tile.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum TileEffect {
+    Hurt(u32),
+    /// Falls down or burns
+    Kill,
+    /// Catches fire while flying over lava
+    Ignite,
+    /// Loses turns and stops burning
+    Soak,
+    /// Keeps moving in the same direction
+    Slide,
+}
+
+/// Effect of the tile on the piece standing on it, flying pieces are safe above most hazards.
+pub fn tile_effect(tile: &TileType, is_flying: bool) -> Option<TileEffect> {
+    match (tile, is_flying) {
+        (TileType::Spikes, false) => Some(TileEffect::Hurt(SPIKES_DAMAGE)),
+        (TileType::Pit | TileType::Lava, false) => Some(TileEffect::Kill),
+        (TileType::Lava, true) => Some(TileEffect::Ignite),
+        (TileType::Water, false) => Some(TileEffect::Soak),
+        (TileType::Ice, false) => Some(TileEffect::Slide),
+        _ => None,
+    }
+}
+
+/// Next tile of the piece sliding in the direction, `None` when it hits something.
+///
+/// Sliding pieces do not stop before the hazards, they can fall into the pits and lava.
+pub fn slide_target(
+    board: &impl BoardState,
+    from: Vector2Int,
+    direction: Vector2Int,
+) -> Option<Vector2Int> {
+    let target = from + direction;
+    (board.tile(target).is_some_and(|t| !t.blocks_movement()) && board.occupant(target).is_none())
+        .then_some(target)
+}
+
+/// None of the tiles between the points blocks the sight.
+pub fn line_of_sight(board: &impl BoardState, from: Vector2Int, to: Vector2Int) -> bool {
+    let (dx, dy) = ((to.x - from.x).abs(), -(to.y - from.y).abs());
+    let (sx, sy) = ((to.x - from.x).signum(), (to.y - from.y).signum());
+    let (mut pos, mut error) = (from, dx + dy);
+    loop {
+        let doubled = error * 2;
+        if doubled >= dy {
+            error += dy;
+            pos.x += sx;
+        }
+        if doubled <= dx {
+            error += dx;
+            pos.y += sy;
+        }
+        if pos == to {
+            return true;
+        }
+        if board.tile(pos).is_none_or(|t| t.blocks_sight()) {
+            return false;
+        }
+    }
+}
+
+/// Returns the piece that would be hit by the attacker standing on `from`.
+pub fn melee_target<B: BoardState>(
+    board: &B,
+    attacker: &Piece,
+    from: Vector2Int,
+    target: Vector2Int,
+) -> Option<B::Id> {
+    if from.manhattan(target) > 1 {
+        return None;
+    }
+    let id = board.occupant(target)?;
+    board
+        .living_piece(id)
+        .is_some_and(|piece| piece != attacker)
+        .then_some(id)
+}
+
+/// Health left after taking the damage, zero means the piece is dead.
+pub fn apply_damage(health: u32, damage: u32) -> u32 {
+    health.saturating_sub(damage)
+}
+
+/// Picks the actor with the lowest turn value that is awake or close enough to the player.
+pub fn next_actor<Id>(
+    actors: impl Iterator<Item = (Id, u64, Vector2Int, bool)>,
+    player: Vector2Int,
+) -> Option<Id> {
+    let mut next: Option<(Id, u64)> = None;
+    for (id, turn, pos, awake) in actors {
+        if !awake && pos.manhattan(player) > ACTIVE_DISTANCE {
+            continue;
+        }
+        if next.as_ref().is_none_or(|(_, lowest)| turn < *lowest) {
+            next = Some((id, turn));
+        }
+    }
+    next.map(|(id, _)| id)
+}
+
+/// Path that avoids other pieces and traps, the `end` tile can be occupied.
+pub fn find_path(
+    board: &impl BoardState,
+    start: Vector2Int,
+    end: Vector2Int,
+    is_flying: bool,
+    max_distance: usize,
+) -> Option<VecDeque<Vector2Int>> {
+    path_avoiding(board, start, end, is_flying, max_distance, |_| true)
+}
+
+/// Path of the player, only the traps the player knows about are avoided.
+///
+/// Going around the hidden ones would give them away.
+pub fn find_player_path(
+    board: &impl BoardState,
+    start: Vector2Int,
+    end: Vector2Int,
+    is_flying: bool,
+    max_distance: usize,
+) -> Option<VecDeque<Vector2Int>> {
+    path_avoiding(board, start, end, is_flying, max_distance, Trap::is_known)
+}
+
+fn path_avoiding(
+    board: &impl BoardState,
+    start: Vector2Int,
+    end: Vector2Int,
+    is_flying: bool,
+    max_distance: usize,
+    avoids: impl Fn(&Trap) -> bool,
+) -> Option<VecDeque<Vector2Int>> {
+    crate::vectors::utils::find_path(start, end, max_distance, |v| {
+        let blocked = board.occupant(v).is_some() || board.trap(v).is_some_and(&avoids);
+        if blocked && v != end {
+            return None;
+        }
+        board.tile(v)?.move_cost(is_flying)
+    })
+}
+
+/// AI attacks whenever it can, otherwise follows the path to the player.
+///
+/// Returns the index of the chosen action.
+pub fn ai_choice(
+    actions: impl Iterator<Item = (ActionType, Option<Vector2Int>)>,
+    path_to_player: Option<&VecDeque<Vector2Int>>,
+) -> Option<usize> {
+    let mut choice = None;
+    for (index, (action_type, target)) in actions.enumerate() {
+        match action_type {
+            ActionType::MeleeeHit => return Some(index),
+            ActionType::Walk
+                if target.is_some_and(|t| path_to_player.is_some_and(|p| p.contains(&t))) =>
+            {
+                choice = Some(index);
+            }
+            _ => {}
+        }
+    }
+    choice
+}
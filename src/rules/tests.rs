@@ -0,0 +1,239 @@
+use std::collections::{HashMap, VecDeque};
+
+use super::*;
+
+/// Board kept in plain data, pieces are identified by their index.
+#[derive(Default)]
+struct TestBoard {
+    tiles: HashMap<Vector2Int, TileType>,
+    traps: HashMap<Vector2Int, Trap>,
+    /// Dead pieces stay on the list
+    pieces: Vec<(Piece, Vector2Int, bool)>,
+}
+
+impl BoardState for TestBoard {
+    type Id = usize;
+
+    fn tile(&self, pos: Vector2Int) -> Option<&TileType> {
+        self.tiles.get(&pos)
+    }
+
+    fn occupant(&self, pos: Vector2Int) -> Option<usize> {
+        self.pieces
+            .iter()
+            .position(|(_, p, alive)| *p == pos && *alive)
+    }
+
+    fn living_piece(&self, id: usize) -> Option<&Piece> {
+        self.pieces
+            .get(id)
+            .filter(|(_, _, alive)| *alive)
+            .map(|(piece, _, _)| piece)
+    }
+
+    fn trap(&self, pos: Vector2Int) -> Option<&Trap> {
+        self.traps.get(&pos).filter(|t| t.armed)
+    }
+}
+
+/// Board from the rows, `.` is the floor, `#` is a wall, `P` is the player and `E` an enemy.
+/// Other tiles use the glyphs of the level files.
+fn board(rows: &[&str]) -> TestBoard {
+    let mut board = TestBoard::default();
+    for (y, row) in rows.iter().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            let pos = Vector2Int::new(x as i32, y as i32);
+            let tile = match c {
+                '#' => continue,
+                'p' => TileType::Pit,
+                '%' => TileType::Lava,
+                '_' => TileType::Ice,
+                'd' => TileType::Door { open: false },
+                _ => TileType::BaseFloor,
+            };
+            board.tiles.insert(pos, tile);
+            match c {
+                'P' => board.pieces.push((Piece::Player, pos, true)),
+                'E' => board.pieces.push((Piece::Enemy, pos, true)),
+                _ => {}
+            }
+        }
+    }
+    board
+}
+
+fn at(x: i32, y: i32) -> Vector2Int {
+    Vector2Int::new(x, y)
+}
+
+#[test]
+fn walk_only_on_free_floor() {
+    let mut board = board(&["P.E#p..."]);
+    assert!(can_walk(&board, at(1, 0)));
+    assert!(!can_walk(&board, at(2, 0)), "occupied");
+    assert!(!can_walk(&board, at(3, 0)), "wall");
+    assert!(!can_walk(&board, at(4, 0)), "pit");
+    board.traps.insert(at(5, 0), Trap::new(TrapKind::Dart));
+    assert!(can_walk(&board, at(5, 0)), "hidden trap");
+    board.traps.get_mut(&at(5, 0)).unwrap().discovered = true;
+    assert!(!can_walk(&board, at(5, 0)), "known trap");
+}
+
+#[test]
+fn dead_pieces_free_the_tile() {
+    let mut board = board(&["PE"]);
+    board.pieces[1].2 = false;
+    assert!(can_walk(&board, at(1, 0)));
+}
+
+#[test]
+fn melee_hits_only_adjacent_living_enemies() {
+    let mut board = board(&["PE.E", "E..."]);
+    let (player, enemy, far_enemy, other_enemy) = (0, 1, 2, 3);
+    assert_eq!(
+        melee_target(&board, &Piece::Player, at(0, 0), at(1, 0)),
+        Some(enemy)
+    );
+    assert_eq!(
+        melee_target(&board, &Piece::Player, at(0, 0), at(0, 1)),
+        Some(other_enemy)
+    );
+    assert_eq!(
+        melee_target(&board, &Piece::Player, at(0, 0), at(3, 0)),
+        None,
+        "too far"
+    );
+    assert_eq!(
+        melee_target(&board, &Piece::Enemy, at(2, 0), at(3, 0)),
+        None,
+        "enemies do not hit each other"
+    );
+    assert_eq!(
+        melee_target(&board, &Piece::Enemy, at(1, 0), at(0, 0)),
+        Some(player)
+    );
+    board.pieces[far_enemy].2 = false;
+    assert_eq!(
+        melee_target(&board, &Piece::Player, at(2, 0), at(3, 0)),
+        None
+    );
+}
+
+#[test]
+fn next_actor_has_the_lowest_turn() {
+    let player = at(0, 0);
+    let actors = [
+        (0, 4, player, false),
+        (1, 2, at(1, 0), false),
+        (2, 3, at(2, 0), false),
+    ];
+    assert_eq!(next_actor(actors.into_iter(), player), Some(1));
+}
+
+#[test]
+fn next_actor_keeps_the_order_on_ties() {
+    let player = at(0, 0);
+    let actors = [(0, 1, player, false), (1, 1, at(1, 0), false)];
+    assert_eq!(next_actor(actors.into_iter(), player), Some(0));
+}
+
+#[test]
+fn next_actor_skips_far_sleeping_monsters() {
+    let player = at(0, 0);
+    let far = at(ACTIVE_DISTANCE + 1, 0);
+    let actors = [(0, 4, player, false), (1, 2, far, false)];
+    assert_eq!(next_actor(actors.into_iter(), player), Some(0));
+    let awake = [(0, 4, player, false), (1, 2, far, true)];
+    assert_eq!(next_actor(awake.into_iter(), player), Some(1));
+}
+
+#[test]
+fn ai_attacks_first() {
+    let path = VecDeque::from([at(1, 0)]);
+    let actions = [
+        (ActionType::Walk, Some(at(1, 0))),
+        (ActionType::MeleeeHit, Some(at(0, 1))),
+    ];
+    assert_eq!(ai_choice(actions.into_iter(), Some(&path)), Some(1));
+}
+
+#[test]
+fn ai_follows_the_path() {
+    let path = VecDeque::from([at(0, 1), at(0, 2)]);
+    let actions = [
+        (ActionType::Walk, Some(at(1, 0))),
+        (ActionType::Walk, Some(at(0, 1))),
+        (ActionType::Search, None),
+    ];
+    assert_eq!(ai_choice(actions.into_iter(), Some(&path)), Some(1));
+    assert_eq!(ai_choice(actions.into_iter(), None), None, "no path");
+}
+
+#[test]
+fn slide_stops_before_walls_and_pieces() {
+    let board = board(&["P__#", "__E.", "_p.d"]);
+    assert_eq!(
+        slide_target(&board, at(1, 0), Vector2Int::RIGHT),
+        Some(at(2, 0))
+    );
+    assert_eq!(
+        slide_target(&board, at(2, 0), Vector2Int::RIGHT),
+        None,
+        "wall"
+    );
+    assert_eq!(
+        slide_target(&board, at(1, 1), Vector2Int::RIGHT),
+        None,
+        "enemy"
+    );
+    assert_eq!(
+        slide_target(&board, at(2, 2), Vector2Int::RIGHT),
+        None,
+        "closed door"
+    );
+    assert_eq!(
+        slide_target(&board, at(0, 2), Vector2Int::RIGHT),
+        Some(at(1, 2)),
+        "pieces slide into the pits"
+    );
+}
+
+#[test]
+fn hazards_spare_the_flying_pieces() {
+    assert_eq!(tile_effect(&TileType::Lava, false), Some(TileEffect::Kill));
+    assert_eq!(tile_effect(&TileType::Lava, true), Some(TileEffect::Ignite));
+    assert_eq!(tile_effect(&TileType::Pit, true), None);
+    assert_eq!(tile_effect(&TileType::Ice, true), None);
+    assert_eq!(
+        tile_effect(&TileType::Spikes, false),
+        Some(TileEffect::Hurt(SPIKES_DAMAGE))
+    );
+}
+
+#[test]
+fn teleport_lands_on_free_floor() {
+    let mut board = board(&["P.E", "p.%"]);
+    board.traps.insert(at(1, 0), Trap::new(TrapKind::Teleport));
+    let target = teleport_target(&board, board.tiles.iter(), 7, 3, at(1, 0));
+    assert_eq!(target, Some(at(1, 1)), "the only free floor without a trap");
+    let again = teleport_target(&board, board.tiles.iter(), 7, 3, at(1, 0));
+    assert_eq!(again, target, "same target in the replay");
+}
+
+#[test]
+fn player_path_goes_over_hidden_traps() {
+    let mut board = board(&["P..", ".#."]);
+    board.traps.insert(at(1, 0), Trap::new(TrapKind::Dart));
+    let straight = Some(VecDeque::from([at(1, 0), at(2, 0)]));
+    assert_eq!(
+        find_player_path(&board, at(0, 0), at(2, 0), false, 5),
+        straight
+    );
+    assert_eq!(
+        find_path(&board, at(0, 0), at(2, 0), false, 5),
+        None,
+        "monsters know their traps"
+    );
+    board.traps.get_mut(&at(1, 0)).unwrap().discovered = true;
+    assert_eq!(find_player_path(&board, at(0, 0), at(2, 0), false, 5), None);
+}
@@ -0,0 +1,212 @@
+use bevy::prelude::*;
+use bevy_third_person_camera::{ThirdPersonCamera, Zoom};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::animations::AnimationSpeed,
+    gui::HelpDisplayEnabled,
+    input::{GamepadBindings, KeyBindings},
+    lights::Torch,
+    states::WaitForAnimations,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+const SETTINGS_PATH: &str = "settings.ron";
+#[cfg(target_arch = "wasm32")]
+const STORAGE_KEY: &str = "rougepush_settings";
+/// Closest the camera can get to the player, the furthest one is set by the player.
+pub const CAMERA_MIN_ZOOM: f32 = 4.5;
+/// Smallest gap between the start and the end of the fog.
+const MIN_FOG_DEPTH: f32 = 1.0;
+
+/// Player preferences, loaded at startup and saved every time they change.
+#[derive(Resource, Reflect, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct GameSettings {
+    /// Furthest zoom of the camera
+    pub camera_distance: f32,
+    /// Multiplier of the mouse orbit and zoom speed
+    pub camera_sensitivity: f32,
+    pub fog_start: f32,
+    pub fog_end: f32,
+    pub torch_shadows: bool,
+    pub ui_scale: f32,
+    /// Multiplier of the piece animations speed
+    pub animation_speed: f32,
+    pub show_help: bool,
+    pub wait_for_animations: bool,
+    pub bindings: KeyBindings,
+    pub gamepad_bindings: GamepadBindings,
+}
+
+impl Default for GameSettings {
+    fn default() -> Self {
+        Self {
+            camera_distance: 7.0,
+            camera_sensitivity: 1.0,
+            fog_start: 5.0,
+            fog_end: 11.5,
+            torch_shadows: true,
+            ui_scale: 1.0,
+            animation_speed: 1.0,
+            show_help: true,
+            wait_for_animations: true,
+            bindings: KeyBindings::default(),
+            gamepad_bindings: GamepadBindings::default(),
+        }
+    }
+}
+
+impl GameSettings {
+    /// Keeps the values in ranges that make sense, edited files can contain anything.
+    pub fn clamp(&mut self) {
+        self.bindings.add_missing();
+        self.gamepad_bindings.add_missing();
+        self.camera_distance = self.camera_distance.clamp(CAMERA_MIN_ZOOM + 0.5, 15.0);
+        self.camera_sensitivity = self.camera_sensitivity.clamp(0.25, 3.0);
+        self.fog_end = self.fog_end.clamp(MIN_FOG_DEPTH, 30.0);
+        self.fog_start = self.fog_start.clamp(0.0, self.fog_end - MIN_FOG_DEPTH);
+        self.ui_scale = self.ui_scale.clamp(0.5, 2.0);
+        self.animation_speed = self.animation_speed.clamp(0.25, 4.0);
+    }
+
+    fn from_ron(content: &str) -> Self {
+        let mut settings = ron::from_str::<Self>(content).unwrap_or_else(|e| {
+            warn!("Cannot parse the settings, using the defaults: {}", e);
+            Self::default()
+        });
+        settings.clamp();
+        settings
+    }
+
+    fn to_ron(&self) -> Option<String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| error!("Cannot serialize the settings: {}", e))
+            .ok()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load() -> Self {
+        match std::fs::read_to_string(SETTINGS_PATH) {
+            Ok(content) => Self::from_ron(&content),
+            Err(_) => Self::default(),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self) {
+        let Some(content) = self.to_ron() else {
+            return;
+        };
+        if let Err(e) = std::fs::write(SETTINGS_PATH, content) {
+            error!("Cannot save the settings to {}: {}", SETTINGS_PATH, e);
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn load() -> Self {
+        Self::storage()
+            .and_then(|s| s.get_item(STORAGE_KEY).ok()?)
+            .map_or_else(Self::default, |content| Self::from_ron(&content))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn save(&self) {
+        let (Some(storage), Some(content)) = (Self::storage(), self.to_ron()) else {
+            return;
+        };
+        if storage.set_item(STORAGE_KEY, &content).is_err() {
+            error!("Cannot save the settings to the local storage");
+        }
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GameSettings>()
+            .insert_resource(GameSettings::load())
+            .add_systems(
+                Update,
+                (apply_settings, save_settings).run_if(resource_changed::<GameSettings>),
+            )
+            .add_systems(Update, apply_torch_shadows);
+    }
+}
+
+fn apply_settings(
+    settings: Res<GameSettings>,
+    mut camera: Query<(&mut ThirdPersonCamera, &mut DistanceFog)>,
+    mut ui_scale: ResMut<UiScale>,
+    mut help: ResMut<HelpDisplayEnabled>,
+    mut wait: ResMut<WaitForAnimations>,
+    mut animation_speed: ResMut<AnimationSpeed>,
+) {
+    for (mut camera, mut fog) in camera.iter_mut() {
+        // the zoom is reset when it is replaced, so it is only done when the range changes
+        if camera.zoom.max != settings.camera_distance {
+            camera.zoom = Zoom::new(CAMERA_MIN_ZOOM, settings.camera_distance);
+        }
+        camera.mouse_sensitivity = settings.camera_sensitivity;
+        camera.zoom_sensitivity = settings.camera_sensitivity;
+        fog.falloff = FogFalloff::Linear {
+            start: settings.fog_start,
+            end: settings.fog_end,
+        };
+    }
+    ui_scale.0 = settings.ui_scale;
+    help.0 = settings.show_help;
+    **wait = settings.wait_for_animations;
+    animation_speed.0 = settings.animation_speed;
+}
+
+fn save_settings(settings: Res<GameSettings>) {
+    // the settings were just loaded, there is nothing new to save
+    if settings.is_added() {
+        return;
+    }
+    settings.save();
+}
+
+/// Torches are spawned with every level, so they are updated when added too.
+fn apply_torch_shadows(
+    settings: Res<GameSettings>,
+    mut torches: Query<(&mut PointLight, Ref<Torch>)>,
+) {
+    for (mut light, torch) in torches.iter_mut() {
+        if (settings.is_changed() || torch.is_added())
+            && light.shadows_enabled != settings.torch_shadows
+        {
+            light.shadows_enabled = settings.torch_shadows;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::keyboard::KeyCode;
+
+    use super::*;
+    use crate::input::InputAction;
+
+    #[test]
+    fn rebind_keys() {
+        let mut settings = GameSettings::default();
+        settings
+            .bindings
+            .rebind(InputAction::Left, 1, KeyCode::KeyW);
+        let saved = ron::to_string(&settings).unwrap();
+        let loaded: GameSettings = ron::from_str(&saved).unwrap();
+        assert_eq!(
+            loaded.bindings.inputs(InputAction::Left),
+            &[KeyCode::ArrowLeft, KeyCode::KeyW]
+        );
+        assert_eq!(loaded.bindings.inputs(InputAction::Up), &[KeyCode::ArrowUp]);
+    }
+}
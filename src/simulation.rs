@@ -0,0 +1,220 @@
+//! Headless runner that plays seeded games without rendering and reports the results as CSV.
+//!
+//! `rougepush --simulate <games> [--seed <first seed>] [--policy greedy|random]
+//! [--max-turns <turns>] [--output <file.csv>]`
+use std::io::Write;
+
+use bevy::log::{
+    info,
+    tracing_subscriber::{self, EnvFilter},
+};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{
+    actions::ActionType,
+    board::{components::*, state::WorldBoard},
+    headless::{HeadlessGame, PlayerOption},
+    rules::BoardState,
+    vectors::utils::find_path,
+};
+
+/// Monster types reported in the kills columns, the game has a single one so far.
+const MONSTERS: [Piece; 1] = [Piece::Enemy];
+
+/// How the simulated player picks actions.
+#[derive(Clone, Copy, Debug)]
+pub enum PlayerPolicy {
+    /// Attacks when possible, otherwise walks towards the closest enemy opening the doors
+    /// and disarming the known traps on the way
+    Greedy,
+    /// Picks any of the possible actions
+    Random,
+}
+
+/// The dungeon has no stairs yet, every game is played on a single floor.
+#[derive(Debug)]
+enum Outcome {
+    /// All of the monsters on the floor are dead
+    Win,
+    Death,
+    /// Out of turns or stuck without a useful action
+    Timeout,
+}
+
+struct GameReport {
+    seed: u64,
+    outcome: Outcome,
+    turns: u64,
+    damage_taken: u32,
+    kills: Vec<usize>,
+}
+
+impl GameReport {
+    fn csv_header() -> String {
+        let kills = MONSTERS
+            .iter()
+            .map(|m| format!(",kills_{:?}", m).to_lowercase())
+            .collect::<String>();
+        format!("seed,outcome,turns,damage_taken{}", kills)
+    }
+
+    fn csv_row(&self) -> String {
+        let kills = self
+            .kills
+            .iter()
+            .map(|k| format!(",{}", k))
+            .collect::<String>();
+        format!(
+            "{},{:?},{},{}{}",
+            self.seed, self.outcome, self.turns, self.damage_taken, kills
+        )
+    }
+}
+
+fn arg_value<T: std::str::FromStr>(args: &[String], name: &str) -> Option<T> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .and_then(|v| v.parse().ok())
+}
+
+pub fn run() {
+    // the games run without the LogPlugin, only the progress of the runner is shown by default
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new("warn,rougepush::simulation=info"));
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .try_init();
+    let args: Vec<String> = std::env::args().collect();
+    let games: u64 = arg_value(&args, "--simulate").unwrap_or(100);
+    let first_seed: u64 = arg_value(&args, "--seed").unwrap_or_else(rand::random);
+    let max_turns: u64 = arg_value(&args, "--max-turns").unwrap_or(500);
+    let policy = match arg_value::<String>(&args, "--policy").as_deref() {
+        Some("random") => PlayerPolicy::Random,
+        _ => PlayerPolicy::Greedy,
+    };
+    let mut output: Box<dyn Write> = match arg_value::<String>(&args, "--output") {
+        Some(path) => Box::new(std::fs::File::create(path).expect("Cannot create the output file")),
+        None => Box::new(std::io::stdout()),
+    };
+
+    info!(
+        "Simulating {} games from seed {} with {:?} policy",
+        games, first_seed, policy
+    );
+    writeln!(output, "{}", GameReport::csv_header()).expect("Cannot write the report");
+    let mut wins = 0;
+    for seed in first_seed..first_seed.saturating_add(games) {
+        let report = play(seed, policy, max_turns);
+        if matches!(report.outcome, Outcome::Win) {
+            wins += 1;
+        }
+        writeln!(output, "{}", report.csv_row()).expect("Cannot write the report");
+    }
+    info!("Won {} out of {} games", wins, games);
+}
+
+fn play(seed: u64, policy: PlayerPolicy, max_turns: u64) -> GameReport {
+    let mut game = HeadlessGame::from_seed(seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let start_health = game.player_health();
+    let spawned = game
+        .world()
+        .get_resource::<CurrentBoard>()
+        .map(|b| b.spawn_points.values().cloned().collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    let mut turns = 0;
+    let outcome = loop {
+        if !game.run_until_player_turn() {
+            break match game.player() {
+                Some(_) => Outcome::Timeout,
+                None => Outcome::Death,
+            };
+        }
+        if game.enemies_alive() == 0 && turns > 0 {
+            break Outcome::Win;
+        }
+        if turns >= max_turns {
+            break Outcome::Timeout;
+        }
+        let options = game.player_options();
+        let choice = match policy {
+            PlayerPolicy::Random => options.choose(&mut rng),
+            PlayerPolicy::Greedy => {
+                greedy_action(&mut game, &options).or_else(|| options.choose(&mut rng))
+            }
+        };
+        let Some(key) = choice.and_then(|o| o.key) else {
+            break Outcome::Timeout;
+        };
+        game.press(key);
+        turns += 1;
+    };
+
+    let world = game.world();
+    let alive = world
+        .query::<&Piece>()
+        .iter(world)
+        .cloned()
+        .collect::<Vec<_>>();
+    let kills = MONSTERS
+        .iter()
+        .map(|m| {
+            let count = |pieces: &[Piece]| pieces.iter().filter(|p| *p == m).count();
+            count(&spawned).saturating_sub(count(&alive))
+        })
+        .collect();
+
+    GameReport {
+        seed,
+        outcome,
+        turns,
+        damage_taken: start_health.saturating_sub(game.player_health()),
+        kills,
+    }
+}
+
+fn greedy_action<'a>(
+    game: &mut HeadlessGame,
+    options: &'a [PlayerOption],
+) -> Option<&'a PlayerOption> {
+    let find = |action: ActionType| options.iter().find(|o| o.action == action);
+    find(ActionType::MeleeeHit)
+        .or_else(|| walk_to_closest_enemy(game, options))
+        .or_else(|| find(ActionType::OpenDoor))
+}
+
+fn walk_to_closest_enemy<'a>(
+    game: &mut HeadlessGame,
+    options: &'a [PlayerOption],
+) -> Option<&'a PlayerOption> {
+    let position = game.player_pos()?;
+    let world = game.world();
+    let closest = world
+        .query::<(&PiecePos, &Piece)>()
+        .iter(world)
+        .filter(|(_, piece)| *piece != &Piece::Player)
+        .map(|(pos, _)| pos.0)
+        .min_by_key(|pos| pos.manhattan(position))?;
+    let max_distance = world.get_resource::<CurrentBoard>()?.tiles.len();
+    let board = WorldBoard(world);
+    // closed doors are on the way, they are opened when reached
+    let path = find_path(position, closest, max_distance, |pos| {
+        if board.occupant(pos).is_some() && pos != closest {
+            return None;
+        }
+        match board.tile(pos)? {
+            TileType::Door { .. } | TileType::LockedDoor => Some(2),
+            tile => tile.move_cost(false),
+        }
+    })?;
+    let next_step = path.front()?;
+    options.iter().find(|o| {
+        matches!(
+            o.action,
+            ActionType::Walk | ActionType::OpenDoor | ActionType::DisarmTrap
+        ) && o.target == Some(*next_step)
+    })
+}
@@ -0,0 +1,187 @@
+use std::collections::{HashSet, VecDeque};
+
+use bevy::{picking::mesh_picking::MeshPickingPlugin, prelude::*};
+use bevy_third_person_camera::ThirdPersonCamera;
+use leafwing_input_manager::action_state::ActionState;
+
+use super::{
+    replay::ReplayPlayer, CurrentActorToken, GamePause, GameTurnSteps, MainGameState,
+    PendingActions, PossibleActions,
+};
+use crate::{
+    actions::ActionType,
+    board::{chunks::MapChunk, components::*, renderer, state::BoardQuery},
+    gui::tooltip,
+    input::InputAction,
+    messages::{MessageCategory, MessageLog},
+    rules,
+    states::TurnCounter,
+    vectors::Vector2Int,
+};
+
+/// Furthest tile that can be clicked, counted in steps.
+const MAX_PATH_DISTANCE: usize = 20;
+/// Actions taken by the click, in order of preference for the same tile.
+const CLICK_ACTIONS: [ActionType; 3] = [
+    ActionType::MeleeeHit,
+    ActionType::OpenDoor,
+    ActionType::Walk,
+];
+
+/// Steps left to the clicked tile, one is taken every player turn.
+#[derive(Resource, Default)]
+pub struct ClickPath {
+    steps: VecDeque<Vector2Int>,
+    /// Enemies seen when the tile was clicked, new ones stop the walk
+    seen_enemies: HashSet<Entity>,
+}
+
+impl ClickPath {
+    fn clear(&mut self) {
+        self.steps.clear();
+        self.seen_enemies.clear();
+    }
+}
+
+pub struct ClickMovePlugin;
+
+impl Plugin for ClickMovePlugin {
+    fn build(&self, app: &mut App) {
+        // the clicks reach the chunks and the piece sprites only with the mesh backend
+        app.add_plugins(MeshPickingPlugin)
+            .init_resource::<ClickPath>()
+            .add_observer(on_board_click)
+            .add_systems(OnEnter(MainGameState::Game), clear_path)
+            .add_systems(
+                Update,
+                follow_path.before(super::select_action).run_if(
+                    in_state(GameTurnSteps::ActionSelection)
+                        .and(in_state(GamePause::Running))
+                        .and(not(resource_exists::<ReplayPlayer>)),
+                ),
+            );
+    }
+}
+
+fn clear_path(mut path: ResMut<ClickPath>) {
+    path.clear();
+}
+
+fn visible_enemies(
+    board: &BoardQuery,
+    player: Vector2Int,
+    enemies: &Query<(Entity, &PiecePos), (With<AiControl>, With<Piece>)>,
+) -> HashSet<Entity> {
+    enemies
+        .iter()
+        .filter(|(_, pos)| renderer::visibility(board, player, pos.0) == Visibility::Inherited)
+        .map(|(e, _)| e)
+        .collect()
+}
+
+/// Clicked or touched tile becomes the target, pieces are picked by their sprites.
+///
+/// Clicks on the chunks are cast onto the floor plane like the tooltip, so the walls standing
+/// in front of the tile do not change the target.
+fn on_board_click(
+    mut trigger: Trigger<Pointer<Click>>,
+    chunks: Query<(), With<MapChunk>>,
+    pieces: Query<&PiecePos>,
+    player: Query<(&PiecePos, Has<Flying>), With<PlayerControl>>,
+    enemies: Query<(Entity, &PiecePos), (With<AiControl>, With<Piece>)>,
+    camera: Query<(&ThirdPersonCamera, &Camera, &GlobalTransform)>,
+    state: Option<Res<State<GamePause>>>,
+    replay: Option<Res<ReplayPlayer>>,
+    board: BoardQuery,
+    mut path: ResMut<ClickPath>,
+) {
+    let event = trigger.event();
+    if event.button != PointerButton::Primary
+        || !state.is_some_and(|s| *s.get() == GamePause::Running)
+        || replay.is_some()
+        // the mouse orbits the camera when the cursor is locked
+        || camera.iter().any(|(c, _, _)| c.cursor_lock_active)
+    {
+        return;
+    }
+    let floor = camera.single().ok().and_then(|(_, camera, transform)| {
+        tooltip::floor_tile(camera, transform, event.pointer_location.position)
+    });
+    let target = match (pieces.get(trigger.target()), floor) {
+        (Ok(pos), _) => pos.0,
+        (_, Some(tile)) if chunks.contains(trigger.target()) => tile,
+        _ => return,
+    };
+    trigger.propagate(false);
+    let Ok((player, flying)) = player.single() else {
+        return;
+    };
+    path.clear();
+    if target == player.0 || renderer::visibility(&board, player.0, target) != Visibility::Inherited
+    {
+        return;
+    }
+    let Some(steps) = rules::find_player_path(&board, player.0, target, flying, MAX_PATH_DISTANCE)
+    else {
+        return;
+    };
+    path.steps = steps;
+    path.seen_enemies = visible_enemies(&board, player.0, &enemies);
+}
+
+/// Takes the next step of the path on the player turn, keys and new enemies interrupt it.
+fn follow_path(
+    mut q: Query<
+        (&PiecePos, &mut PossibleActions, &ActionState<InputAction>),
+        (With<CurrentActorToken>, With<PlayerControl>),
+    >,
+    enemies: Query<(Entity, &PiecePos), (With<AiControl>, With<Piece>)>,
+    board: BoardQuery,
+    mut path: ResMut<ClickPath>,
+    mut log: ResMut<MessageLog>,
+    turn: Res<TurnCounter>,
+    mut next_state: ResMut<NextState<GameTurnSteps>>,
+    mut action_queue: ResMut<PendingActions>,
+) {
+    let Some(step) = path.steps.front().copied() else {
+        return;
+    };
+    let Ok((pos, mut actions, action_state)) = q.single_mut() else {
+        return;
+    };
+    if !action_state.get_just_pressed().is_empty() {
+        path.clear();
+        return;
+    }
+    let new_enemy = visible_enemies(&board, pos.0, &enemies)
+        .iter()
+        .any(|e| !path.seen_enemies.contains(e));
+    if new_enemy {
+        log.push(
+            **turn,
+            MessageCategory::Danger,
+            "You stop, an enemy comes into view",
+        );
+        path.clear();
+        return;
+    }
+    let index = CLICK_ACTIONS.iter().find_map(|action_type| {
+        actions.iter().position(|a| {
+            a.action_type() == *action_type
+                && a.target_pos() == Some(step)
+                && a.get_input().is_some()
+        })
+    });
+    // something blocks the path now
+    let Some(index) = index else {
+        path.clear();
+        return;
+    };
+    let action = actions.0.remove(index);
+    // the opened door is walked through in the next turn
+    if action.action_type() != ActionType::OpenDoor {
+        path.steps.pop_front();
+    }
+    action_queue.push_back(action);
+    next_state.set(GameTurnSteps::PerformAction);
+}
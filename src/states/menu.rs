@@ -1,7 +1,8 @@
 use std::time::Duration;
 
-use crate::gui::extra::button::ButtonReleased;
-use crate::states::MainGameState;
+use crate::board::components::RunSeed;
+use crate::gui::{extra::button::ButtonReleased, settings::OpenSettings};
+use crate::states::{practice::PracticeMode, MainGameState};
 use crate::{consts, ObserverExtension};
 use bevy::prelude::*;
 use bevy_tweening::lens::TransformScaleLens;
@@ -10,10 +11,15 @@ use bevy_tweening::{Animator, Tween};
 #[derive(Component)]
 pub enum MainMenuButton {
     StartGame,
+    Practice,
+    Settings,
     #[cfg(not(target_arch = "wasm32"))]
     Exit,
 }
 
+#[derive(Component)]
+pub struct MenuRoot;
+
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
@@ -37,7 +43,7 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
             },
             StateScoped(MainGameState::Menu),
         ))
-        .insert(Name::new("menu-root"))
+        .insert((Name::new("menu-root"), MenuRoot))
         .with_children(|parent| {
             parent.spawn((
                 TextFont {
@@ -77,6 +83,22 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
                         ..default()
                     },
                 ),
+                (
+                    "Practice",
+                    MainMenuButton::Practice,
+                    UiRect {
+                        bottom: Val::Px(15.0),
+                        ..default()
+                    },
+                ),
+                (
+                    "Settings",
+                    MainMenuButton::Settings,
+                    UiRect {
+                        bottom: Val::Px(15.0),
+                        ..default()
+                    },
+                ),
                 #[cfg(not(target_arch = "wasm32"))]
                 (
                     "Exit Game",
@@ -129,13 +151,31 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
 
 fn on_menu_button(
     t: Trigger<ButtonReleased>,
+    mut commands: Commands,
     q: Query<&MainMenuButton>,
+    root: Query<Entity, With<MenuRoot>>,
     mut next_state: ResMut<NextState<MainGameState>>,
+    mut practice: ResMut<PracticeMode>,
+    mut seed: ResMut<RunSeed>,
     #[cfg(not(target_arch = "wasm32"))] mut exit: EventWriter<bevy::app::AppExit>,
 ) {
     if let Ok(button_type) = q.get(t.target()) {
         match *button_type {
-            MainMenuButton::StartGame => next_state.set(MainGameState::Game),
+            MainMenuButton::StartGame => {
+                practice.0 = false;
+                *seed = RunSeed::default();
+                next_state.set(MainGameState::Game);
+            }
+            MainMenuButton::Practice => {
+                practice.0 = true;
+                *seed = RunSeed::default();
+                next_state.set(MainGameState::Game);
+            }
+            MainMenuButton::Settings => {
+                if let Ok(root) = root.single() {
+                    commands.trigger_targets(OpenSettings, root);
+                }
+            }
             #[cfg(not(target_arch = "wasm32"))]
             MainMenuButton::Exit => {
                 exit.write(bevy::app::AppExit::Success);
@@ -1,19 +1,30 @@
+pub mod click_move;
 pub mod menu;
-use bevy::platform::collections::{HashMap, HashSet};
+pub mod pause;
+pub mod practice;
+pub mod replay;
 use bevy::{ecs::system::SystemParam, prelude::*};
 use leafwing_input_manager::action_state::ActionState;
-use std::collections::{BinaryHeap, VecDeque};
+use std::collections::VecDeque;
 use std::ops::DerefMut;
 
 use crate::{
-    actions::{melee_hit::MeleeHitAction, walk::WalkAction, Action, ActionType, RegisterActions},
-    board::components::*,
+    actions::{
+        blast_wall::BlastWallAction, disarm_trap::DisarmTrapAction, melee_hit::MeleeHitAction,
+        open_door::OpenDoorAction, search::SearchAction, walk::WalkAction, Action, RegisterActions,
+    },
+    board::{components::*, state::BoardQuery},
     despawn_recursive_by_component,
     input::InputAction,
-    vectors::{Vector2Int, ORTHO_DIRECTIONS},
+    messages::{self, MessageLog},
+    rules,
+    vectors::Vector2Int,
 };
 
-use self::menu::MenuPlugin;
+use self::{
+    practice::PracticePlugin,
+    replay::{ActionLog, ReplayPlayer, ReplayPlugin},
+};
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash, Default, States)]
 pub enum MainGameState {
@@ -21,6 +32,17 @@ pub enum MainGameState {
     AssetLoading,
     Menu,
     Game,
+    /// Leaves the game and starts it again in the next frame, the seed stays the same.
+    Restart,
+}
+
+/// The turns are not taken while the game is paused.
+#[derive(Debug, Hash, PartialEq, Eq, Default, Clone, SubStates)]
+#[source(MainGameState = MainGameState::Game)]
+pub enum GamePause {
+    #[default]
+    Running,
+    Paused,
 }
 
 #[derive(Debug, Hash, PartialEq, Eq, Default, Clone, SubStates)]
@@ -55,14 +77,28 @@ pub struct PendingActions(pub VecDeque<Box<dyn Action>>);
 #[derive(Deref, DerefMut, Component, Default, Reflect)]
 pub struct ActorTurn(pub u64);
 
+/// Pending actions wait until the pieces finish their animations.
+#[derive(Resource, Reflect, Deref, DerefMut)]
+pub struct WaitForAnimations(pub bool);
+
+impl Default for WaitForAnimations {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Amount of turns taken by all actors in the current game.
+#[derive(Default, Resource, Reflect, Deref, DerefMut)]
+pub struct TurnCounter(pub u64);
+
 #[derive(Event, Default, Reflect)]
 pub struct PlayerIsDeadEvent;
 
 #[derive(SystemParam)]
 pub struct IngameActors<'w, 's> {
-    pub q: Query<'w, 's, (Entity, &'static ActorTurn, &'static PiecePos), With<Piece>>,
+    pub q: Query<'w, 's, (Entity, &'static ActorTurn, &'static PiecePos, Has<Awake>), With<Piece>>,
     player_q: Query<'w, 's, &'static PiecePos, With<PlayerControl>>,
-    pub turn: Local<'s, u64>,
+    pub turn: ResMut<'w, TurnCounter>,
 }
 
 impl IngameActors<'_, '_> {
@@ -70,22 +106,14 @@ impl IngameActors<'_, '_> {
         let Ok(player) = self.player_q.single() else {
             return None;
         };
-        let (mut lowest_delay, mut l_entity) = (u64::MAX, Entity::PLACEHOLDER);
-        for (entity, delay, pos) in self.q.iter() {
-            if pos.manhattan(**player) > 5 {
-                continue;
-            }
-            if **delay < lowest_delay {
-                lowest_delay = **delay;
-                l_entity = entity;
-            }
-        }
-        if lowest_delay < u64::MAX {
-            *self.turn += 1;
-            Some(l_entity)
-        } else {
-            None
-        }
+        let next = rules::next_actor(
+            self.q
+                .iter()
+                .map(|(entity, turn, pos, awake)| (entity, **turn, pos.0, awake)),
+            player.0,
+        )?;
+        **self.turn += 1;
+        Some(next)
     }
 }
 
@@ -93,18 +121,29 @@ pub struct GameStatesPlugin;
 
 impl Plugin for GameStatesPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(MenuPlugin)
+        app.add_plugins((PracticePlugin, ReplayPlugin))
             .init_state::<MainGameState>()
             .add_sub_state::<GameTurnSteps>()
+            .add_sub_state::<GamePause>()
             .enable_state_scoped_entities::<MainGameState>()
             .register_type::<CurrentActorToken>()
             .add_event::<PlayerIsDeadEvent>()
             .register_type::<ActorTurn>()
+            .register_type::<TurnCounter>()
+            .init_resource::<TurnCounter>()
+            .register_type::<MessageLog>()
+            .init_resource::<MessageLog>()
+            .add_systems(OnEnter(MainGameState::Game), messages::clear_log)
+            .register_type::<WaitForAnimations>()
+            .init_resource::<WaitForAnimations>()
             .register_all_actions()
             .init_resource::<PendingActions>()
+            .add_systems(OnEnter(MainGameState::Restart), restart_game)
             .add_systems(
                 Update,
-                find_actor.run_if(in_state(GameTurnSteps::SearchForAgents)),
+                find_actor.run_if(
+                    in_state(GameTurnSteps::SearchForAgents).and(in_state(GamePause::Running)),
+                ),
             )
             .configure_sets(
                 OnEnter(GameTurnSteps::ActionSelection),
@@ -130,13 +169,17 @@ impl Plugin for GameStatesPlugin {
             )
             .add_systems(
                 Update,
-                (select_action, ai_select_action)
-                    .chain()
-                    .run_if(in_state(GameTurnSteps::ActionSelection)),
+                (select_action, ai_select_action).chain().run_if(
+                    in_state(GameTurnSteps::ActionSelection)
+                        .and(in_state(GamePause::Running))
+                        .and(not(resource_exists::<ReplayPlayer>)),
+                ),
             )
             .add_systems(
                 Update,
-                execute_pending_action.run_if(in_state(GameTurnSteps::PerformAction)),
+                execute_pending_action.run_if(
+                    in_state(GameTurnSteps::PerformAction).and(in_state(GamePause::Running)),
+                ),
             )
             .add_systems(
                 Update,
@@ -155,9 +198,18 @@ impl Plugin for GameStatesPlugin {
     }
 }
 
-fn remove_map(mut commands: Commands, mut next: ResMut<NextState<GameTurnSteps>>) {
+fn remove_map(
+    mut commands: Commands,
+    mut next: ResMut<NextState<GameTurnSteps>>,
+    mut turn: ResMut<TurnCounter>,
+) {
     next.set(GameTurnSteps::SearchForAgents);
     commands.remove_resource::<CurrentBoard>();
+    **turn = 0;
+}
+
+fn restart_game(mut next_state: ResMut<NextState<MainGameState>>) {
+    next_state.set(MainGameState::Game);
 }
 
 fn find_actor(query: Query<(Entity, &Piece)>, mut next_state: ResMut<NextState<GameTurnSteps>>) {
@@ -172,7 +224,7 @@ fn set_current_actor(mut commands: Commands, mut actors: IngameActors) {
     };
     commands
         .entity(next_actor)
-        .insert((CurrentActorToken, ActorTurn(*actors.turn)));
+        .insert((CurrentActorToken, ActorTurn(**actors.turn)));
 }
 
 fn prepare_action_list(world: &mut World) {
@@ -197,6 +249,9 @@ fn prepare_action_list(world: &mut World) {
         let walk = WalkAction(entity, target_pos, key_code);
 
         possible_actions.push(Box::new(walk));
+        possible_actions.push(Box::new(OpenDoorAction(entity, target_pos, key_code)));
+        possible_actions.push(Box::new(DisarmTrapAction(entity, target_pos, key_code)));
+        possible_actions.push(Box::new(BlastWallAction(entity, target_pos, key_code)));
 
         if let Some(melee_attack) = melee {
             let attack = MeleeHitAction {
@@ -209,6 +264,7 @@ fn prepare_action_list(world: &mut World) {
             possible_actions.push(Box::new(attack));
         }
     }
+    possible_actions.push(Box::new(SearchAction(entity, InputAction::Search)));
     world
         .entity_mut(entity)
         .insert(PossibleActions(possible_actions))
@@ -262,51 +318,46 @@ fn select_action(
 
 fn ai_select_action(
     mut q: Query<
-        (&PiecePos, &mut PossibleActions, &AiControl, Option<&Flying>),
+        (
+            &PiecePos,
+            &mut PossibleActions,
+            &AiControl,
+            Option<&Flying>,
+            Has<Awake>,
+        ),
         With<CurrentActorToken>,
     >,
     mut next_state: ResMut<NextState<GameTurnSteps>>,
     player_query: Query<(&PiecePos, &Piece), With<PlayerControl>>,
     mut action_queue: ResMut<PendingActions>,
-    occupier_query: Query<&PiecePos, With<Occupier>>,
-    board: Option<Res<CurrentBoard>>,
+    board: BoardQuery,
 ) {
-    let Ok((position, mut actions, ai, flying)) = q.single_mut() else {
+    let Ok((position, mut actions, ai, flying, awake)) = q.single_mut() else {
         return;
     };
     let Ok((player_position, _)) = player_query.single() else {
         return;
     };
-    let Some(board) = board else {
-        return;
-    };
-    let mut action_index = None;
 
     // find possible path to the player
-    let path_to_player = find_path(
+    let path_to_player = rules::find_path(
+        &board,
         position.0,
         player_position.0,
-        &board.tiles.clone(),
-        &occupier_query.iter().map(|p| p.0).collect(),
         flying.is_some(),
-        ai.max_distance_to_player,
+        if awake {
+            rules::AWAKE_PATH_DISTANCE
+        } else {
+            ai.max_distance_to_player
+        },
     );
     info!("Path to the player: {:?}", path_to_player);
-    for (index, action) in actions.0.iter().enumerate() {
-        if action.action_type() == ActionType::MeleeeHit {
-            action_index = Some(index);
-            break;
-        }
-        if action.action_type() == ActionType::Walk {
-            if let Some(path) = &path_to_player {
-                if path.contains(&action.target_pos().unwrap()) {
-                    action_index = Some(index);
-                }
-            }
-        }
-    }
-    if action_index.is_some() {
-        let action_moved = actions.0.remove(action_index.unwrap());
+    let action_index = rules::ai_choice(
+        actions.iter().map(|a| (a.action_type(), a.target_pos())),
+        path_to_player.as_ref(),
+    );
+    if let Some(index) = action_index {
+        let action_moved = actions.0.remove(index);
         // info!(
         //     "ACTION SELECTED: {:?} -> {:?}",
         //     action_moved.action_type(),
@@ -317,7 +368,13 @@ fn ai_select_action(
     next_state.set(GameTurnSteps::PerformAction);
 }
 
-fn execute_pending_action(world: &mut World) {
+pub fn execute_pending_action(world: &mut World) {
+    if **world.resource::<WaitForAnimations>() {
+        let mut animating = world.query_filtered::<(), With<Animating>>();
+        if animating.iter(world).next().is_some() {
+            return;
+        }
+    }
     let Some(mut actions) = world.get_resource_mut::<PendingActions>() else {
         return;
     };
@@ -329,13 +386,22 @@ fn execute_pending_action(world: &mut World) {
         state.set(GameTurnSteps::ActionSelection);
         return;
     };
-    if !action.execute(world) {
+    let actor = action
+        .actor()
+        .and_then(|e| world.get::<PiecePos>(e))
+        .map(|p| p.0);
+    let result = action.execute(world);
+    if !result {
         error!(
             "Error during action: {:?} -> {:?}",
             action.action_type(),
             action.target_pos()
         );
     };
+    let turn = **world.resource::<TurnCounter>();
+    world
+        .resource_mut::<ActionLog>()
+        .record(turn, action.as_ref(), actor, result);
 }
 
 fn remove_token(mut commands: Commands, query: Query<Entity, With<CurrentActorToken>>) {
@@ -345,64 +411,6 @@ fn remove_token(mut commands: Commands, query: Query<Entity, With<CurrentActorTo
     commands.entity(entity).remove::<CurrentActorToken>();
 }
 
-pub fn find_path(
-    start: Vector2Int,
-    end: Vector2Int,
-    tiles: &HashMap<Vector2Int, TileType>,
-    blockers: &HashSet<Vector2Int>,
-    is_flying: bool,
-    max_distance: usize,
-) -> Option<VecDeque<Vector2Int>> {
-    let mut queue = BinaryHeap::new();
-    queue.push(crate::vectors::utils::Node { v: start, cost: 0 });
-    let mut visited = HashMap::new();
-    visited.insert(start, 0);
-    let mut came_from = HashMap::new();
-
-    while let Some(crate::vectors::utils::Node { v, cost }) = queue.pop() {
-        if v == end {
-            break;
-        }
-        for dir in ORTHO_DIRECTIONS {
-            let n = v + dir;
-            let new_cost = cost + 1;
-            if !tiles.contains_key(&n) {
-                continue;
-            }
-            match (&tiles[&n], is_flying) {
-                (&TileType::Pit, false) => continue,
-                (&TileType::None, _) => continue,
-                _ => {}
-            }
-            // we allow the target to be a blocker
-            if blockers.contains(&n) && n != end {
-                continue;
-            }
-            match visited.get(&n) {
-                Some(c) if *c <= new_cost => (),
-                _ => {
-                    visited.insert(n, new_cost);
-                    queue.push(crate::vectors::utils::Node {
-                        v: n,
-                        cost: new_cost,
-                    });
-                    came_from.insert(n, v);
-                }
-            }
-        }
-    }
-    let mut path = VecDeque::new();
-    let mut cur = end;
-    while let Some(v) = came_from.get(&cur) {
-        path.push_front(cur);
-        cur = *v;
-        if cur == start && path.len() <= max_distance {
-            return Some(path);
-        }
-    }
-    None
-}
-
 fn check_if_player_is_alive(
     mut removed: RemovedComponents<Piece>,
     player_query: Query<&PlayerControl>,
@@ -0,0 +1,159 @@
+use bevy::prelude::*;
+use leafwing_input_manager::action_state::ActionState;
+
+use super::{GamePause, MainGameState};
+use crate::{
+    board::components::RunSeed,
+    consts,
+    gui::extra::button::ButtonReleased,
+    gui::settings::{settings_closed, OpenSettings},
+    input::InputAction,
+    ObserverExtension,
+};
+
+#[derive(Component, Clone, Copy)]
+pub enum PauseMenuButton {
+    Resume,
+    /// Starts the same run again, with the same seed.
+    Restart,
+    NewRun,
+    Settings,
+    Quit,
+}
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.enable_state_scoped_entities::<GamePause>()
+            .add_systems(
+                Update,
+                toggle_pause.run_if(in_state(MainGameState::Game).and(settings_closed)),
+            )
+            .add_systems(OnEnter(GamePause::Paused), (setup_pause_menu, pause_time))
+            .add_systems(OnExit(GamePause::Paused), resume_time);
+    }
+}
+
+fn toggle_pause(
+    input: Query<&ActionState<InputAction>>,
+    state: Res<State<GamePause>>,
+    mut next_state: ResMut<NextState<GamePause>>,
+) {
+    if !input.iter().any(|a| a.just_pressed(&InputAction::Pause)) {
+        return;
+    }
+    next_state.set(match state.get() {
+        GamePause::Running => GamePause::Paused,
+        GamePause::Paused => GamePause::Running,
+    });
+}
+
+/// Animations and timers stop together with the turns.
+fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+fn resume_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+fn setup_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let btn_text_style = (
+        TextFont {
+            font: asset_server.load(consts::BASE_FONT),
+            font_size: 25.0,
+            ..default()
+        },
+        TextColor(Srgba::hex("CFC6B8").unwrap().into()),
+    );
+    commands
+        .spawn((
+            BackgroundColor(Color::BLACK.with_alpha(0.6)),
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(15.0),
+                ..default()
+            },
+            GlobalZIndex(5),
+            StateScoped(GamePause::Paused),
+            Name::new("pause-root"),
+        ))
+        .with_children(|root| {
+            root.spawn((
+                Text::new("Paused"),
+                TextFont {
+                    font: asset_server.load(consts::BASE_FONT),
+                    font_size: 45.0,
+                    ..default()
+                },
+                TextColor(consts::MY_ACCENT_COLOR),
+            ));
+            for (text, button) in [
+                ("Resume", PauseMenuButton::Resume),
+                ("Restart", PauseMenuButton::Restart),
+                ("New run", PauseMenuButton::NewRun),
+                ("Settings", PauseMenuButton::Settings),
+                ("Quit to menu", PauseMenuButton::Quit),
+            ] {
+                root.spawn((
+                    Button,
+                    ImageNode {
+                        color: Srgba::hex("7A444A").unwrap().into(),
+                        image: asset_server.load("ui/panel-024.png"),
+                        image_mode: bevy::ui::widget::NodeImageMode::Sliced(TextureSlicer {
+                            border: BorderRect::all(22.0),
+                            center_scale_mode: SliceScaleMode::Stretch,
+                            sides_scale_mode: SliceScaleMode::Stretch,
+                            max_corner_scale: 1.0,
+                        }),
+                        ..default()
+                    },
+                    Node {
+                        width: Val::Px(250.0),
+                        height: Val::Px(60.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    Name::new(format!("button:{}", text)),
+                    button,
+                ))
+                .observe_in_child(on_pause_button)
+                .with_child((Text::new(text), btn_text_style.clone()));
+            }
+        });
+}
+
+fn on_pause_button(
+    t: Trigger<ButtonReleased>,
+    mut commands: Commands,
+    q: Query<&PauseMenuButton>,
+    root: Query<Entity, With<StateScoped<GamePause>>>,
+    mut pause: ResMut<NextState<GamePause>>,
+    mut next_state: ResMut<NextState<MainGameState>>,
+    mut seed: ResMut<RunSeed>,
+) {
+    let Ok(button) = q.get(t.target()) else {
+        return;
+    };
+    match button {
+        PauseMenuButton::Resume => pause.set(GamePause::Running),
+        PauseMenuButton::Restart => next_state.set(MainGameState::Restart),
+        PauseMenuButton::NewRun => {
+            *seed = RunSeed::default();
+            next_state.set(MainGameState::Restart);
+        }
+        PauseMenuButton::Settings => {
+            if let Ok(root) = root.single() {
+                commands.trigger_targets(OpenSettings, root);
+            }
+        }
+        PauseMenuButton::Quit => next_state.set(MainGameState::Menu),
+    }
+}
@@ -0,0 +1,218 @@
+use bevy::prelude::*;
+use leafwing_input_manager::action_state::ActionState;
+
+use super::{
+    replay::ActionLog, ActorTurn, CurrentActorToken, GamePause, GameTurnSteps, MainGameState,
+    PreparingActions, TurnCounter,
+};
+use crate::{board::components::*, input::InputAction};
+
+/// How many player turns can be undone in a row.
+const MAX_UNDO_TURNS: usize = 32;
+
+/// When enabled the player can undo turns.
+#[derive(Resource, Reflect, Default, PartialEq, Eq)]
+pub struct PracticeMode(pub bool);
+
+/// State of a single piece at the start of the turn.
+struct PieceSnapshot {
+    entity: Entity,
+    pos: PiecePos,
+    turn: u64,
+    piece: Piece,
+    health: Option<u32>,
+    keys: Option<u32>,
+    bombs: Option<u32>,
+    burning: Option<u32>,
+    awake: bool,
+}
+
+/// All of the pieces, traps and the board at the start of the turn.
+struct TurnSnapshot {
+    pieces: Vec<PieceSnapshot>,
+    traps: Vec<(Entity, Trap)>,
+    board: Option<CurrentBoard>,
+    turn: u64,
+    /// Amount of recorded actions, the undone ones are removed from the replay
+    logged_actions: usize,
+}
+
+#[derive(Resource, Default)]
+pub struct TurnHistory {
+    /// Snapshot taken before the next actor was picked
+    pending: Option<TurnSnapshot>,
+    /// Snapshots taken at the start of every player turn, the last one is the current turn
+    turns: Vec<TurnSnapshot>,
+}
+
+pub struct PracticePlugin;
+
+impl Plugin for PracticePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PracticeMode>()
+            .init_resource::<PracticeMode>()
+            .init_resource::<TurnHistory>()
+            .add_systems(
+                OnEnter(GameTurnSteps::ActionSelection),
+                (
+                    take_snapshot.before(PreparingActions::SetCurrentActor),
+                    store_player_turn
+                        .after(PreparingActions::SetCurrentActor)
+                        .before(PreparingActions::PrepareActionList),
+                )
+                    .run_if(resource_equals(PracticeMode(true))),
+            )
+            .add_systems(
+                Update,
+                undo_last_turn.run_if(
+                    in_state(GameTurnSteps::ActionSelection)
+                        .and(in_state(GamePause::Running))
+                        .and(resource_equals(PracticeMode(true))),
+                ),
+            )
+            .add_systems(OnExit(MainGameState::Game), clear_history);
+    }
+}
+
+fn take_snapshot(
+    mut history: ResMut<TurnHistory>,
+    q: Query<(
+        Entity,
+        &PiecePos,
+        &ActorTurn,
+        &Piece,
+        Option<&Health>,
+        Option<&Keys>,
+        Option<&Bombs>,
+        Option<&Burning>,
+        Has<Awake>,
+    )>,
+    traps: Query<(Entity, &Trap)>,
+    board: Option<Res<CurrentBoard>>,
+    turn: Res<TurnCounter>,
+    log: Res<ActionLog>,
+) {
+    let pieces = q
+        .iter()
+        .map(
+            |(entity, pos, turn, piece, health, keys, bombs, burning, awake)| PieceSnapshot {
+                entity,
+                pos: *pos,
+                turn: **turn,
+                piece: piece.clone(),
+                health: health.map(|h| h.value),
+                keys: keys.map(|k| k.0),
+                bombs: bombs.map(|b| b.0),
+                burning: burning.map(|b| b.0),
+                awake,
+            },
+        )
+        .collect();
+    history.pending = Some(TurnSnapshot {
+        pieces,
+        traps: traps.iter().map(|(e, t)| (e, t.clone())).collect(),
+        board: board.map(|b| b.clone()),
+        turn: **turn,
+        logged_actions: log.actions.len(),
+    });
+}
+
+fn store_player_turn(
+    mut history: ResMut<TurnHistory>,
+    q: Query<(), (With<CurrentActorToken>, With<PlayerControl>)>,
+) {
+    if q.is_empty() {
+        return;
+    }
+    let Some(snapshot) = history.pending.take() else {
+        return;
+    };
+    if history.turns.len() >= MAX_UNDO_TURNS {
+        history.turns.remove(0);
+    }
+    history.turns.push(snapshot);
+}
+
+fn undo_last_turn(
+    mut commands: Commands,
+    mut history: ResMut<TurnHistory>,
+    q: Query<&ActionState<InputAction>, (With<CurrentActorToken>, With<PlayerControl>)>,
+    mut next_state: ResMut<NextState<GameTurnSteps>>,
+    current: Option<ResMut<CurrentBoard>>,
+    mut tile_changes: EventWriter<TileChanged>,
+    mut turn: ResMut<TurnCounter>,
+    mut log: ResMut<ActionLog>,
+) {
+    let Ok(action_state) = q.single() else {
+        return;
+    };
+    if !action_state.just_released(&InputAction::Undo) || history.turns.len() < 2 {
+        return;
+    }
+    // the last snapshot is the turn that is happening right now
+    history.turns.pop();
+    let Some(TurnSnapshot {
+        pieces,
+        traps,
+        board,
+        turn: snapshot_turn,
+        logged_actions,
+    }) = history.turns.pop()
+    else {
+        return;
+    };
+    info!("Undo last turn");
+    // the replay continues as if the undone turns never happened
+    **turn = snapshot_turn;
+    log.actions.truncate(logged_actions);
+    // free all of the tiles first, so pieces can swap places without overlapping
+    for snapshot in pieces.iter() {
+        commands.entity(snapshot.entity).remove::<Occupier>();
+    }
+    for snapshot in pieces {
+        let mut entity = commands.entity(snapshot.entity);
+        entity.insert((
+            snapshot.pos,
+            ActorTurn(snapshot.turn),
+            snapshot.piece,
+            Occupier,
+        ));
+        if let Some(value) = snapshot.health {
+            entity.insert(Health { value });
+        }
+        if let Some(keys) = snapshot.keys {
+            entity.insert(Keys(keys));
+        }
+        if let Some(bombs) = snapshot.bombs {
+            entity.insert(Bombs(bombs));
+        }
+        match snapshot.burning {
+            Some(turns) => entity.insert(Burning(turns)),
+            None => entity.remove::<Burning>(),
+        };
+        if snapshot.awake {
+            entity.insert(Awake);
+        } else {
+            entity.remove::<Awake>();
+        }
+    }
+    for (entity, trap) in traps {
+        commands.entity(entity).insert(trap);
+    }
+    // brings back the closed doors and the picked up items
+    if let (Some(board), Some(mut current)) = (board, current) {
+        let changed = board
+            .tiles
+            .iter()
+            .filter(|(pos, tile)| current.tiles.get(*pos) != Some(*tile))
+            .map(|(pos, _)| TileChanged(*pos));
+        tile_changes.write_batch(changed);
+        *current = board;
+    }
+    // pass the empty action queue so the turn starts again from the restored state
+    next_state.set(GameTurnSteps::PerformAction);
+}
+
+fn clear_history(mut history: ResMut<TurnHistory>) {
+    *history = TurnHistory::default();
+}
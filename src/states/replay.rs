@@ -0,0 +1,262 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    practice::PracticeMode, CurrentActorToken, GamePause, GameTurnSteps, MainGameState,
+    PendingActions, TurnCounter,
+};
+use crate::{
+    actions::{
+        blast_wall::BlastWallAction, disarm_trap::DisarmTrapAction, melee_hit::MeleeHitAction,
+        open_door::OpenDoorAction, search::SearchAction, walk::WalkAction, Action, ActionType,
+    },
+    board::components::*,
+    input::InputAction,
+    vectors::Vector2Int,
+};
+
+/// Time between the actions when the replay is not played step by step.
+const REPLAY_STEP: Duration = Duration::from_millis(300);
+#[cfg(not(target_arch = "wasm32"))]
+const REPLAYS_DIR: &str = "replays";
+
+/// Single executed action.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ActionRecord {
+    /// Value of the [`TurnCounter`] when the action was executed
+    pub turn: u64,
+    pub action: ActionType,
+    /// Position of the actor, `None` for consequences of other actions
+    pub actor: Option<Vector2Int>,
+    pub target: Option<Vector2Int>,
+    pub key: Option<InputAction>,
+    pub result: bool,
+}
+
+/// All of the actions executed during the current run.
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct ActionLog {
+    pub seed: u64,
+    pub actions: Vec<ActionRecord>,
+}
+
+/// Feeds the recorded actions back through [`PendingActions`] instead of the player and AI.
+#[derive(Resource)]
+pub struct ReplayPlayer {
+    pub seed: u64,
+    pub actions: VecDeque<ActionRecord>,
+    /// Wait for the `Enter` key before every action instead of the timer
+    pub step_by_step: bool,
+    pub timer: Timer,
+}
+
+impl ReplayPlayer {
+    pub fn new(log: ActionLog, step_by_step: bool) -> Self {
+        Self {
+            seed: log.seed,
+            actions: log.actions.into(),
+            step_by_step,
+            timer: Timer::new(REPLAY_STEP, TimerMode::Repeating),
+        }
+    }
+
+    /// Reads the replay passed with `--replay <file>`, `--step` plays it step by step.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().collect();
+        let path = args
+            .iter()
+            .position(|a| a == "--replay")
+            .and_then(|i| args.get(i + 1))?;
+        let log = match std::fs::read_to_string(path) {
+            Ok(content) => ron::from_str::<ActionLog>(&content),
+            Err(e) => {
+                error!("Cannot read the replay {}: {}", path, e);
+                return None;
+            }
+        };
+        match log {
+            Ok(log) => Some(Self::new(log, args.iter().any(|a| a == "--step"))),
+            Err(e) => {
+                error!("Cannot parse the replay {}: {}", path, e);
+                None
+            }
+        }
+    }
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionLog>()
+            .add_systems(OnEnter(MainGameState::Menu), start_replay)
+            .add_systems(OnEnter(MainGameState::Game), clear_log)
+            .add_systems(
+                Update,
+                replay_select_action.run_if(
+                    in_state(GameTurnSteps::ActionSelection)
+                        .and(in_state(GamePause::Running))
+                        .and(resource_exists::<ReplayPlayer>),
+                ),
+            )
+            .add_systems(OnExit(MainGameState::Game), stop_replay);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(replay) = ReplayPlayer::from_args() {
+                app.insert_resource(replay);
+            }
+            app.add_systems(OnExit(MainGameState::Game), save_log.before(stop_replay));
+        }
+    }
+}
+
+impl ActionLog {
+    /// Stores the action executed in the world, `actor` is the position before the execution.
+    pub fn record(
+        &mut self,
+        turn: u64,
+        action: &dyn Action,
+        actor: Option<Vector2Int>,
+        result: bool,
+    ) {
+        self.actions.push(ActionRecord {
+            turn,
+            action: action.action_type(),
+            actor,
+            target: action.target_pos(),
+            key: action.get_input(),
+            result,
+        });
+    }
+}
+
+fn start_replay(
+    replay: Option<Res<ReplayPlayer>>,
+    mut seed: ResMut<RunSeed>,
+    mut practice: ResMut<PracticeMode>,
+    mut next_state: ResMut<NextState<MainGameState>>,
+) {
+    let Some(replay) = replay else {
+        return;
+    };
+    info!(
+        "Starting replay with seed {}, {} actions",
+        replay.seed,
+        replay.actions.len()
+    );
+    *seed = RunSeed(replay.seed);
+    practice.0 = false;
+    next_state.set(MainGameState::Game);
+}
+
+fn clear_log(mut log: ResMut<ActionLog>, seed: Res<RunSeed>) {
+    *log = ActionLog {
+        seed: **seed,
+        actions: Vec::new(),
+    };
+}
+
+fn replay_select_action(
+    mut commands: Commands,
+    mut replay: ResMut<ReplayPlayer>,
+    q: Query<(Entity, &PiecePos, &Piece, Option<&Melee>), With<CurrentActorToken>>,
+    turn: Res<TurnCounter>,
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameTurnSteps>>,
+    mut action_queue: ResMut<PendingActions>,
+) {
+    let Ok((entity, pos, piece, melee)) = q.single() else {
+        return;
+    };
+    let ready = if replay.step_by_step {
+        keys.just_released(KeyCode::Enter)
+    } else {
+        replay.timer.tick(time.delta()).just_finished()
+    };
+    if !ready {
+        return;
+    }
+    // consequences of other actions are recreated by executing them again
+    while replay.actions.front().is_some_and(|a| a.actor.is_none()) {
+        replay.actions.pop_front();
+    }
+    let Some(record) = replay.actions.front() else {
+        info!("Replay finished");
+        commands.remove_resource::<ReplayPlayer>();
+        return;
+    };
+    let diverged = record.turn < **turn || (record.turn == **turn && record.actor != Some(pos.0));
+    if diverged {
+        warn!("Replay diverged at turn {}: {:?}", **turn, record);
+        commands.remove_resource::<ReplayPlayer>();
+        return;
+    }
+    // the recorded actor did nothing in this turn
+    if record.turn > **turn {
+        next_state.set(GameTurnSteps::PerformAction);
+        return;
+    }
+    let action: Option<Box<dyn Action>> = match (record.action, record.target, record.key) {
+        (ActionType::Walk, Some(target), Some(key)) => {
+            Some(Box::new(WalkAction(entity, target, key)))
+        }
+        (ActionType::OpenDoor, Some(target), Some(key)) => {
+            Some(Box::new(OpenDoorAction(entity, target, key)))
+        }
+        (ActionType::DisarmTrap, Some(target), Some(key)) => {
+            Some(Box::new(DisarmTrapAction(entity, target, key)))
+        }
+        (ActionType::BlastWall, Some(target), Some(key)) => {
+            Some(Box::new(BlastWallAction(entity, target, key)))
+        }
+        (ActionType::Search, _, Some(key)) => Some(Box::new(SearchAction(entity, key))),
+        (ActionType::MeleeeHit, Some(target), key) => melee.map(|melee| {
+            Box::new(MeleeHitAction {
+                attacker: entity,
+                attacker_type: piece.clone(),
+                target,
+                damage: melee.damage,
+                key,
+            }) as Box<dyn Action>
+        }),
+        _ => None,
+    };
+    match action {
+        Some(action) => action_queue.push_back(action),
+        None => warn!("Cannot replay action: {:?}", record),
+    }
+    replay.actions.pop_front();
+    next_state.set(GameTurnSteps::PerformAction);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_log(log: Res<ActionLog>) {
+    if log.actions.is_empty() {
+        return;
+    }
+    let content = match ron::ser::to_string_pretty(&*log, ron::ser::PrettyConfig::default()) {
+        Ok(content) => content,
+        Err(e) => {
+            error!("Cannot serialize the replay: {}", e);
+            return;
+        }
+    };
+    let path = std::path::Path::new(REPLAYS_DIR).join(format!("{}.ron", log.seed));
+    if let Err(e) =
+        std::fs::create_dir_all(REPLAYS_DIR).and_then(|_| std::fs::write(&path, content))
+    {
+        error!("Cannot save the replay {:?}: {}", path, e);
+    } else {
+        info!("Replay saved to {:?}", path);
+    }
+}
+
+fn stop_replay(mut commands: Commands) {
+    commands.remove_resource::<ReplayPlayer>();
+}
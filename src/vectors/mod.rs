@@ -1,9 +1,23 @@
 pub mod utils;
 
 use bevy::prelude::Reflect;
+use serde::{Deserialize, Serialize};
 use std::ops::{Add, AddAssign, Div, Mul, Sub, SubAssign};
 
-#[derive(Copy, Clone, Debug, Default, Ord, PartialOrd, PartialEq, Eq, Hash, Reflect)]
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Default,
+    Ord,
+    PartialOrd,
+    PartialEq,
+    Eq,
+    Hash,
+    Reflect,
+    Serialize,
+    Deserialize,
+)]
 pub struct Vector2Int {
     pub x: i32,
     pub y: i32,
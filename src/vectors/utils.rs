@@ -11,14 +11,24 @@ use super::{Vector2Int, ORTHO_DIRECTIONS};
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub struct Node {
     pub v: Vector2Int,
+    /// estimated total cost of the path going through this node (`g + h`)
     pub cost: u32,
+    /// heuristic part of the cost, used for tie-breaking
+    pub h: u32,
+    /// amount of steps taken to get here
+    pub steps: i32,
 }
 
 impl Ord for Node {
     fn cmp(&self, other: &Self) -> Ordering {
+        // BinaryHeap is a max-heap, so the comparisons are reversed.
+        // On equal cost prefer nodes closer to the goal and shorter paths,
+        // then fall back to the position to keep the search deterministic.
         other
             .cost
             .cmp(&self.cost)
+            .then_with(|| other.h.cmp(&self.h))
+            .then_with(|| other.steps.cmp(&self.steps))
             .then_with(|| self.v.cmp(&other.v))
     }
 }
@@ -28,3 +38,177 @@ impl PartialOrd for Node {
         Some(self.cmp(other))
     }
 }
+
+/// A* search on the grid using the manhattan distance as the heuristic.
+///
+/// `tile_cost` returns the cost of entering a tile or `None` when it cannot be entered.
+/// Costs have to be at least 1 for the heuristic to stay admissible.
+/// Nodes that cannot reach `end` in `max_distance` steps are not expanded.
+/// A tile keeps every way of reaching it that is either cheaper or shorter than the others,
+/// since a cheaper but longer way may leave too few steps to get to `end`.
+///
+/// Returns the path without the `start` tile, ending on `end`.
+pub fn find_path(
+    start: Vector2Int,
+    end: Vector2Int,
+    max_distance: usize,
+    tile_cost: impl Fn(Vector2Int) -> Option<u32>,
+) -> Option<VecDeque<Vector2Int>> {
+    let max_distance = max_distance as i32;
+    if start.manhattan(end) > max_distance || tile_cost(end).is_none() {
+        return None;
+    }
+    let heuristic = |v: Vector2Int| v.manhattan(end) as u32;
+    let mut queue = BinaryHeap::new();
+    queue.push(Node {
+        v: start,
+        cost: heuristic(start),
+        h: heuristic(start),
+        steps: 0,
+    });
+    // cost and amount of steps of the ways to reach every visited tile, none of them is
+    // both more expensive and longer than another one
+    let mut visited: HashMap<Vector2Int, Vec<(u32, i32)>> = HashMap::new();
+    visited.insert(start, vec![(0, 0)]);
+    // previous tile for the tile reached in the given amount of steps
+    let mut came_from = HashMap::new();
+
+    while let Some(Node { v, cost, h, steps }) = queue.pop() {
+        if v == end {
+            let mut path = VecDeque::new();
+            let mut cur = (end, steps);
+            while cur.0 != start {
+                path.push_front(cur.0);
+                cur = (came_from[&cur], cur.1 - 1);
+            }
+            return Some(path);
+        }
+        let g = cost - h;
+        // stale entry, this tile was already reached in a cheaper and shorter way
+        if !visited[&v].contains(&(g, steps)) {
+            continue;
+        }
+        for dir in ORTHO_DIRECTIONS {
+            let n = v + dir;
+            if steps + 1 + n.manhattan(end) > max_distance {
+                continue;
+            }
+            let Some(move_cost) = tile_cost(n) else {
+                continue;
+            };
+            let new = (g + move_cost.max(1), steps + 1);
+            let known = visited.entry(n).or_default();
+            if known.iter().any(|(c, s)| *c <= new.0 && *s <= new.1) {
+                continue;
+            }
+            known.retain(|(c, s)| *c < new.0 || *s < new.1);
+            known.push(new);
+            queue.push(Node {
+                v: n,
+                cost: new.0 + heuristic(n),
+                h: heuristic(n),
+                steps: new.1,
+            });
+            came_from.insert((n, new.1), v);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Grid from the rows, `#` is a wall, digits are entry costs and the rest costs 1.
+    /// `S` and `E` mark the start and the end, tiles outside of the rows are walls.
+    fn grid(rows: &[&str]) -> (Vector2Int, Vector2Int, impl Fn(Vector2Int) -> Option<u32>) {
+        let mut tiles = HashMap::new();
+        let (mut start, mut end) = (Vector2Int::default(), Vector2Int::default());
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                let v = Vector2Int::new(x as i32, y as i32);
+                match c {
+                    'S' => start = v,
+                    'E' => end = v,
+                    _ => (),
+                }
+                if c != '#' {
+                    tiles.insert(v, c.to_digit(10).unwrap_or(1));
+                }
+            }
+        }
+        (start, end, move |v| tiles.get(&v).copied())
+    }
+
+    #[test]
+    fn straight_path() {
+        let (start, end, cost) = grid(&["S...E"]);
+        let path = find_path(start, end, 10, cost).unwrap();
+        assert_eq!(
+            Vec::from(path),
+            (1..5).map(|x| Vector2Int::new(x, 0)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn unreachable_target() {
+        let (start, end, cost) = grid(&["S.#..", "..#.E", "..#.."]);
+        assert_eq!(find_path(start, end, 20, cost), None);
+    }
+
+    #[test]
+    fn blocked_goal() {
+        let (start, _, cost) = grid(&["S...#"]);
+        assert_eq!(find_path(start, Vector2Int::new(4, 0), 20, cost), None);
+    }
+
+    #[test]
+    fn target_too_far() {
+        let (start, end, cost) = grid(&["S.#.E", "....."]);
+        assert_eq!(find_path(start, end, 5, &cost), None);
+        assert_eq!(find_path(start, end, 6, &cost).map(|p| p.len()), Some(6));
+    }
+
+    #[test]
+    fn avoids_expensive_tiles() {
+        let (start, end, cost) = grid(&["S9..E", "....."]);
+        let path = find_path(start, end, 10, cost).unwrap();
+        assert_eq!(path.len(), 6);
+        assert!(!path.contains(&Vector2Int::new(1, 0)));
+    }
+
+    #[test]
+    fn expensive_tiles_within_max_distance() {
+        let (start, end, cost) = grid(&["S9..E", "....."]);
+        let path = find_path(start, end, 4, cost).unwrap();
+        assert_eq!(path.front(), Some(&Vector2Int::new(1, 0)));
+    }
+
+    #[test]
+    fn cheaper_but_longer_path_does_not_hide_the_short_one() {
+        // the tile behind the expensive one is reached cheaper around it,
+        // but from there the rest of the path does not fit in `max_distance`
+        let (start, end, cost) = grid(&["##...", "S5.#E", "...#."]);
+        let path = find_path(start, end, 6, cost).unwrap();
+        assert_eq!(
+            Vec::from(path),
+            vec![
+                Vector2Int::new(1, 1),
+                Vector2Int::new(2, 1),
+                Vector2Int::new(2, 0),
+                Vector2Int::new(3, 0),
+                Vector2Int::new(4, 0),
+                Vector2Int::new(4, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn deterministic_tie_breaking() {
+        let (start, end, cost) = grid(&["S....", ".....", "....E"]);
+        let first = find_path(start, end, 10, &cost);
+        for _ in 0..10 {
+            assert_eq!(find_path(start, end, 10, &cost), first);
+        }
+    }
+}